@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+use crate::loader::ImuData;
+
+/// A stretch of a run which is either all riding or all sat still
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ActivitySegment {
+    /// Time the segment starts at in seconds
+    pub start: f32,
+    /// Time the segment ends at in seconds
+    pub end: f32,
+    pub riding: bool,
+}
+
+/// Finds the portions of a run where the bike is being ridden, as opposed to sat still at the start or the end
+///
+/// The run is split into windows and a window counts as riding when the travel of either end, or the IMU
+/// acceleration, moves around enough in it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct ActivityDetector {
+    /// Length of a window in seconds
+    pub window: f32,
+    /// Standard deviation of the travel in a window, in % travel, above which the window counts as riding
+    pub threshold: f32,
+    /// Standard deviation of the acceleration magnitude in a window, in m/s², above which the window counts as riding
+    pub accel_threshold: f32,
+    /// Idle gaps between riding shorter than this in seconds count as riding, so a short stop does not split a run
+    pub min_idle: f32,
+}
+
+impl Default for ActivityDetector {
+    fn default() -> ActivityDetector {
+        ActivityDetector {
+            window: 1.0,
+            threshold: 1.0,
+            accel_threshold: 2.0,
+            min_idle: 5.0,
+        }
+    }
+}
+
+impl ActivityDetector {
+    /// Number of samples in a window, never less than 2 so a standard deviation can be taken
+    fn window_samples(&self, polling_rate: f32) -> usize {
+        usize::max((self.window * polling_rate) as usize, 2)
+    }
+
+    /// Whether each window of the travel counts as riding, the last partial window is included
+    pub fn active_windows(&self, data: &[f32], polling_rate: f32) -> Vec<bool> {
+        data.chunks(self.window_samples(polling_rate))
+            .map(|window| std_dev(window) > self.threshold)
+            .collect()
+    }
+
+    /// Whether each window of the IMU acceleration counts as riding, the last partial window is included
+    pub fn imu_active_windows(&self, imu: &ImuData) -> Vec<bool> {
+        let magnitudes: Vec<f32> = imu.accel.iter().map(|a| (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt()).collect();
+        magnitudes.chunks(self.window_samples(imu.polling_rate as f32))
+            .map(|window| std_dev(window) > self.accel_threshold)
+            .collect()
+    }
+
+    /// Split a run into riding and idle segments
+    ///
+    /// # Arguments
+    ///
+    /// `travel`: (travel in %, polling rate) of each end
+    /// `imu`: The IMU data of the run, None if the logger has no IMU
+    ///
+    /// # Returns
+    ///
+    /// The segments in time order, alternating between riding and idle and covering the whole run
+    pub fn segments(&self, travel: &[(&[f32], f32)], imu: Option<&ImuData>) -> Vec<ActivitySegment> {
+        let mut channels: Vec<Vec<bool>> = travel.iter().map(|(data, rate)| self.active_windows(data, *rate)).collect();
+        if let Some(imu) = imu {
+            channels.push(self.imu_active_windows(imu));
+        }
+
+        let window_count = channels.iter().map(|c| c.len()).max().unwrap_or(0);
+        let active: Vec<bool> = (0..window_count)
+            .map(|i| channels.iter().any(|c| c.get(i).copied().unwrap_or(false)))
+            .collect();
+        let duration = travel.iter().map(|(data, rate)| data.len() as f32 / rate).fold(0.0, f32::max);
+
+        let mut segments: Vec<ActivitySegment> = Vec::new();
+        for (i, riding) in active.iter().enumerate() {
+            let start = i as f32 * self.window;
+            let end = f32::min(start + self.window, duration);
+            match segments.last_mut() {
+                Some(last) if last.riding == *riding => last.end = end,
+                _ => segments.push(ActivitySegment { start, end, riding: *riding }),
+            }
+        }
+
+        // short stops between riding are part of the ride, those at the ends of the run are kept as idle
+        let last_index = segments.len().saturating_sub(1);
+        for (i, segment) in segments.iter_mut().enumerate() {
+            if !segment.riding && i != 0 && i != last_index && segment.end - segment.start < self.min_idle {
+                segment.riding = true;
+            }
+        }
+
+        segments.into_iter().fold(Vec::new(), |mut merged: Vec<ActivitySegment>, segment| {
+            match merged.last_mut() {
+                Some(last) if last.riding == segment.riding => last.end = segment.end,
+                _ => merged.push(segment),
+            }
+            merged
+        })
+    }
+}
+
+/// Time range from the start of the first riding segment to the end of the last one
+///
+/// # Returns
+///
+/// (start, end) in seconds, None if nothing counts as riding
+pub fn riding_range(segments: &[ActivitySegment]) -> Option<(f32, f32)> {
+    let first = segments.iter().find(|s| s.riding)?;
+    let last = segments.iter().rev().find(|s| s.riding)?;
+    Some((first.start, last.end))
+}
+
+fn std_dev(data: &[f32]) -> f32 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    let mean = data.iter().sum::<f32>() / data.len() as f32;
+    (data.iter().map(|d| (d - mean).powi(2)).sum::<f32>() / data.len() as f32).sqrt()
+}
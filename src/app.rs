@@ -1,23 +1,57 @@
 
-use crate::config_info::{self, ConfigInfo, SuspensionRemapInfo};
+use crate::activity::{riding_range, ActivitySegment};
+use crate::balance;
+use crate::braking_table::BrakingTable;
+use crate::damper;
+use crate::config_info::{self, BikeConfig, ConfigInfo, SuspensionRemapInfo, TravelThresholds, TravelUnit};
+use crate::calibration_window::CalibrationWindow;
+use crate::calibration_wizard::CalibrationWizard;
 use crate::config_window::ConfigWindow;
-use crate::data::{Data, TelemData};
-use crate::graph::bar_graph::BarPoints;
+use crate::data::{sample_range, Data, TelemData};
+use crate::distance::{self, DistanceMap, XAxis};
+use crate::filter::Filter;
+use crate::graph::balance_graph::BalanceGraph;
+use crate::graph::damper_graph::DamperGraph;
+use crate::graph::spring_graph::SpringGraph;
+use crate::graph::time_graph::TimeGraph;
+use crate::graph::bar_graph::{BarPoints, HistogramSettings, Normalisation};
 use crate::graph::disp_vel_graph::DispVelGraph;
 use crate::graph::line_manager::LineManager;
+use crate::graph::spectrum_graph::SpectrumGraph;
 use crate::graph::suspension_graph::SuspensionGraph;
 use crate::graph::wave_gen::WaveGen;
-use crate::graph::to_plot_points;
-use crate::loader::Loader;
+use crate::jump_table::JumpTable;
+use crate::leverage::{self, LeverageCurve};
+use crate::leverage_window::LeverageWindow;
+use crate::sag::{CalibratedSag, EndSag, SagMetrics};
+use crate::packdown;
+use crate::sections::RunAnnotations;
+use crate::spring::{self, SpringModel};
+use crate::sections_window::SectionsWindow;
+use crate::loader::{ImuData, Loader};
+use crate::orientation::Orientation;
+use crate::spectrum;
+use crate::stroke::StrokeDirection;
+use crate::stroke_table::StrokeTable;
+use crate::travel_events::{detect_travel_events, TravelEventKind};
+use crate::travel_stats_window::TravelStatsWindow;
+use crate::wheel_speed;
 use crate::view::View;
 use crate::Buff;
 
 use egui::Color32;
+use egui_plot::PlotPoint;
 use rfd::FileDialog;
 
 use std::env;
+use std::ops::Range;
 use std::path::PathBuf;
 
+/// Points along the spring curve
+const SPRING_CURVE_STEPS: usize = 50;
+/// Bins of the shock stroke histogram drawn over the spring curve
+const SPRING_HISTOGRAM_BINS: usize = 20;
+
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)] // if we add new fields, give them default values when deserializing old state
@@ -36,9 +70,10 @@ pub struct TelemApp<'a> {
     low_adj_str: String,
     #[serde(skip)]
     high_adj_str: String,
+    /// The bike whose settings (thresholds etc.) are used for the analysis
+    current_bike_ref: String,
     #[serde(skip)]
-    bottom_out_threshold: f64,
-    bottom_outs: u32,
+    new_bike_name: String,
     #[serde(skip)]
     telem_data: Data,
     #[serde(skip)]
@@ -48,9 +83,54 @@ pub struct TelemApp<'a> {
     #[serde(skip)]
     config: ConfigInfo,
     show_unmapped_data: bool,
+    travel_unit: TravelUnit,
+    /// The unit the loaded data is actually shown in, None for unmapped data
+    #[serde(skip)]
+    shown_travel_unit: Option<TravelUnit>,
+    /// Whether the time based graphs are plotted against time or distance
+    x_axis: XAxis,
+    /// Where the distance of the loaded run was estimated from, None if it has none
+    #[serde(skip)]
+    distance_source: Option<&'static str>,
+    /// The time range picked by the user to measure over, None to use the detected riding portion
+    #[serde(skip)]
+    selected_range: Option<(f32, f32)>,
+    /// Binning of the rear travel histogram
+    rear_histogram: HistogramSettings,
+    /// Binning of the front travel histogram
+    front_histogram: HistogramSettings,
+    /// The channel the spectrum is computed for, "RS", "FS", another channel tag or an IMU channel name
+    spectrum_channel: String,
+    /// Samples per FFT segment of the spectrum and spectrogram
+    spectrum_segment_len: usize,
+    /// Average overlapping segments into a Welch PSD rather than taking the magnitude spectrum of the whole range
+    spectrum_welch: bool,
+    /// The named section being analysed, takes over from the selected range and is looked up again when a run is
+    /// loaded so the same section can be compared across runs
+    #[serde(skip)]
+    analysed_section: Option<String>,
+    /// The sections and markers of the loaded run
+    #[serde(skip)]
+    annotations: RunAnnotations,
+    #[serde(skip)]
+    sections_window: SectionsWindow,
     #[serde(skip)]
     config_window: ConfigWindow,
     #[serde(skip)]
+    stroke_table: StrokeTable,
+    #[serde(skip)]
+    jump_table: JumpTable,
+    #[serde(skip)]
+    braking_table: BrakingTable,
+    #[serde(skip)]
+    travel_stats_window: TravelStatsWindow,
+    #[serde(skip)]
+    leverage_window: LeverageWindow,
+    #[serde(skip)]
+    calibration_window: CalibrationWindow,
+    #[serde(skip)]
+    calibration_wizard: CalibrationWizard,
+    #[serde(skip)]
     current_remap_info: SuspensionRemapInfo,
     #[serde(skip)]
     current_remap_info_ref: String,
@@ -72,14 +152,34 @@ impl<'a> Default for TelemApp<'a> {
             stroke_len_str: curr_remap_info.stroke_len.to_string(),
             low_adj_str: curr_remap_info.inverse_without_stroke_len_scale(config_info::DEFAULT_SUS_MIN).to_string(),
             high_adj_str: curr_remap_info.inverse_without_stroke_len_scale(config_info::DEFAULT_SUS_MAX).to_string(),
-            bottom_out_threshold: 0.0,
-            bottom_outs: 0,
+            current_bike_ref: "Pick a bike".to_string(),
+            new_bike_name: String::new(),
             loader: Loader::new(),
             telem_data: Data::new(),
             sus_view: View::new(),
             config: ConfigInfo::load(),
             show_unmapped_data: false,
+            travel_unit: TravelUnit::Percent,
+            shown_travel_unit: None,
+            x_axis: XAxis::Time,
+            distance_source: None,
+            selected_range: None,
+            rear_histogram: HistogramSettings::default(),
+            front_histogram: HistogramSettings::default(),
+            spectrum_channel: "RS".to_string(),
+            spectrum_segment_len: 1024,
+            spectrum_welch: true,
+            analysed_section: None,
+            annotations: RunAnnotations::default(),
+            sections_window: SectionsWindow::new(),
             config_window: ConfigWindow::new(),
+            stroke_table: StrokeTable::new(),
+            jump_table: JumpTable::new(),
+            braking_table: BrakingTable::new(),
+            travel_stats_window: TravelStatsWindow::new(),
+            leverage_window: LeverageWindow::new(),
+            calibration_window: CalibrationWindow::new(),
+            calibration_wizard: CalibrationWizard::new(),
             current_remap_info: SuspensionRemapInfo::default(),
             current_remap_info_ref: "Pick a remap reference".to_string(),
         }
@@ -113,20 +213,27 @@ impl<'a> TelemApp<'a> {
     }
 
 
-    pub fn count_bottom_outs(&mut self) {
-        self.bottom_outs = 0;
-        // let mut above_threshold = self.data.data[0] as f64 > self.bottom_out_threshold;
-        // for reading in &self.data.data {
-        //     let reading_as_f64 = *reading as f64;
-        //     if reading_as_f64 > self.bottom_out_threshold {
-        //         if !above_threshold {
-        //             self.bottom_outs += 1;
-        //         }
-        //         above_threshold = true;
-        //     } else {
-        //         above_threshold = false;
-        //     }
-        // }
+    /// The settings of the selected bike, or the defaults if no bike is selected
+    fn current_bike(&self) -> BikeConfig {
+        self.config.get_bike(self.current_bike_ref.clone()).unwrap_or_default()
+    }
+
+    /// Finds the bottom outs and top outs at one end
+    ///
+    /// # Arguments
+    ///
+    /// `end`: "rear" or "front", the events and thresholds are stored as `end` + "_travel_events" etc.
+    /// `data`: The travel
+    /// `polling_rate`: Polling rate of the channel
+    /// `thresholds`: The thresholds, in the same unit as the travel
+    /// `range`: (start, end) in seconds, only events starting in it are kept
+    pub fn count_travel_events(&mut self, end: &str, data: &[f32], polling_rate: f32, thresholds: TravelThresholds, range: (f32, f32)) {
+        let mut events = detect_travel_events(data, polling_rate, &thresholds);
+        events.retain(|e| e.start >= range.0 && e.start < range.1);
+
+        self.telem_data.set(end.to_string() + "_travel_events", TelemData::TravelEventV(events)).unwrap();
+        self.telem_data.set(end.to_string() + "_bottom_out_threshold", TelemData::F32(thresholds.bottom_out)).unwrap();
+        self.telem_data.set(end.to_string() + "_top_out_threshold", TelemData::F32(thresholds.top_out)).unwrap();
     }
 
     pub fn reset_data(&mut self) {
@@ -140,55 +247,487 @@ impl<'a> TelemApp<'a> {
 
         let mut rear_sus_data_f32: Vec<f32> = rs_pot_data.data.iter().map(|d| { *d as f32 }).collect();
         let mut front_sus_data_f32: Vec<f32> = fs_pot_data.data.iter().map(|d| { *d as f32 }).collect();
+        let (rs_polling_rate, fs_polling_rate) = (rs_pot_data.polling_rate as f32, fs_pot_data.polling_rate as f32);
 
-        
+        let mut bike = self.current_bike();
+        // the leverage curve is interpolated by searching its points, which only works while they are sorted, and
+        // the window only sorts them once an edit is finished
+        bike.leverage_curve.sort();
+        let (mut rear_thresholds, mut front_thresholds) = (bike.rear_thresholds, bike.front_thresholds);
+        let (mut rear_stroke_detector, mut front_stroke_detector) = (self.config.stroke_detector, self.config.stroke_detector);
+        let (mut rear_travel_max, mut front_travel_max) = (config_info::MAPPED_MAX, config_info::MAPPED_MAX);
+        // the mapped travel in %, kept when the travel is shown in mm as the sag is measured in both
+        let (mut rear_percent, mut front_percent) = (Vec::new(), Vec::new());
+        let (mut rear_static_sag, mut front_static_sag) = (None, None);
         if !self.show_unmapped_data {
             let rs_remap_info = self.config.get_sus_remap_info(rs_pot_data.remap_ref.clone()).expect("Error: Suspension remap info not found");
             let fs_remap_info = self.config.get_sus_remap_info(fs_pot_data.remap_ref.clone()).expect("Error: Suspension remap info not found");
 
-            rear_sus_data_f32 = self.telem_data.remapped_1d_with_clamp(&rear_sus_data_f32, &rs_remap_info, 0.0, 100.0);
-            front_sus_data_f32 = self.telem_data.remapped_1d_with_clamp(&front_sus_data_f32, &fs_remap_info, 0.0, 100.0);
+            let rs_calibration = self.config.get_calibration(rs_pot_data.remap_ref.clone());
+            let fs_calibration = self.config.get_calibration(fs_pot_data.remap_ref.clone());
+
+            rear_sus_data_f32 = self.telem_data.remapped_1d_with_clamp(&rear_sus_data_f32, &rs_remap_info, rs_calibration, 0.0, 100.0);
+            front_sus_data_f32 = self.telem_data.remapped_1d_with_clamp(&front_sus_data_f32, &fs_remap_info, fs_calibration, 0.0, 100.0);
+            let leverage_curve = Some(&bike.leverage_curve).filter(|c| c.is_valid());
+            self.set_mm_channels("rear".to_string(), &rear_sus_data_f32, &rs_remap_info, leverage_curve);
+            self.set_mm_channels("front".to_string(), &front_sus_data_f32, &fs_remap_info, None);
+            (rear_percent, front_percent) = (rear_sus_data_f32.clone(), front_sus_data_f32.clone());
+            // the static sag from the calibration wizard, for runs which start with riding
+            let static_sag = |remap_info: &SuspensionRemapInfo, curve| remap_info.static_sag
+                .map(|sag| (sag, Some(leverage::remap_wheel_travel_mm(remap_info, curve, sag)).filter(|_| remap_info.has_mm())));
+            (rear_static_sag, front_static_sag) = (static_sag(&rs_remap_info, leverage_curve), static_sag(&fs_remap_info, None));
+
+            self.shown_travel_unit = Some(TravelUnit::Percent);
+            if self.travel_unit == TravelUnit::Millimetres && rs_remap_info.has_mm() && fs_remap_info.has_mm() {
+                self.shown_travel_unit = Some(TravelUnit::Millimetres);
 
-            self.telem_data.set_count("rear_suspension_counts".to_string(), &rear_sus_data_f32, 26, 100.0, false).unwrap();
-            self.telem_data.set_count("front_suspension_counts".to_string(), &front_sus_data_f32, 26, 100.0, false).unwrap();
+                let rear_to_mm = |d: f32| leverage::remap_wheel_travel_mm(&rs_remap_info, leverage_curve, d);
+                let front_to_mm = |d: f32| leverage::remap_wheel_travel_mm(&fs_remap_info, None, d);
+
+                rear_sus_data_f32 = rear_sus_data_f32.iter().map(|d| rear_to_mm(*d)).collect();
+                front_sus_data_f32 = front_sus_data_f32.iter().map(|d| front_to_mm(*d)).collect();
+                rear_thresholds = rear_thresholds.converted(rear_to_mm);
+                front_thresholds = front_thresholds.converted(front_to_mm);
+                rear_stroke_detector = rear_stroke_detector.converted(rear_to_mm);
+                front_stroke_detector = front_stroke_detector.converted(front_to_mm);
+                (rear_travel_max, front_travel_max) = (rear_to_mm(config_info::MAPPED_MAX), front_to_mm(config_info::MAPPED_MAX));
+            }
         } else {
             self.telem_data.set("stroke_len".to_string(), TelemData::F32(config_info::DEFAULT_SUS_MAX - config_info::DEFAULT_SUS_MAX)).unwrap();
-            self.telem_data.set_count("rear_suspension_counts".to_string(), &rear_sus_data_f32, 26, config_info::DEFAULT_SUS_MAX as f64, false).unwrap();
-            self.telem_data.set_count("front_suspension_counts".to_string(), &front_sus_data_f32, 26, config_info::DEFAULT_SUS_MAX as f64, false).unwrap();
+            self.shown_travel_unit = None;
+            (rear_travel_max, front_travel_max) = (config_info::DEFAULT_SUS_MAX, config_info::DEFAULT_SUS_MAX);
         }
-        self.telem_data.set_turning_points("rear_rebound".to_string(), "rear_compression".to_string(), "rear_turning".to_string(), &rear_sus_data_f32, false).unwrap();
-        self.telem_data.set_turning_points("front_rebound".to_string(), "front_compression".to_string(), "front_turning".to_string(), &front_sus_data_f32, true).unwrap();
-        
-        let rear_sus_data_f32_enum = self.telem_data.enumerated_with_transform(&rear_sus_data_f32, 1.0 / rs_pot_data.polling_rate as f32, 0.0);
-        let front_sus_data_f32_enum = self.telem_data.enumerated_with_transform(&front_sus_data_f32, 1.0 / fs_pot_data.polling_rate as f32, 0.0);
 
-        let rear_line_manager = LineManager::new(to_plot_points(&rear_sus_data_f32_enum), rs_pot_data.polling_rate as f64);
-        let front_line_manager = LineManager::new(to_plot_points(&front_sus_data_f32_enum), fs_pot_data.polling_rate as f64);
+        // the riding portion is found from the % travel as the activity thresholds are in %, unmapped data is
+        // analysed whole
+        let segments = if self.show_unmapped_data {
+            Vec::new()
+        } else {
+            let imu = Some(&self.loader.imu).filter(|imu| imu.is_present());
+            self.config.activity_detector.segments(&[(&rear_percent, rs_polling_rate), (&front_percent, fs_polling_rate)], imu)
+        };
+        let duration = f32::max(rear_sus_data_f32.len() as f32 / rs_polling_rate, front_sus_data_f32.len() as f32 / fs_polling_rate);
+        let section_range = self.analysed_section.as_ref()
+            .and_then(|name| self.annotations.find_section(name))
+            .map(|section| (section.start, section.end));
+        let range = section_range.or(self.selected_range).or(riding_range(&segments)).unwrap_or((0.0, duration));
+        let rear_range = sample_range(range, rs_polling_rate, rear_sus_data_f32.len());
+        let front_range = sample_range(range, fs_polling_rate, front_sus_data_f32.len());
+        // the distance comes first as every line is plotted against it when picked
+        let orientation = Some(&self.loader.imu)
+            .filter(|imu| imu.is_present())
+            .map(|imu| self.config.orientation_filter.apply(imu));
+        self.set_distance(orientation.as_ref(), &segments, range);
+        self.telem_data.set("activity_segments".to_string(), TelemData::ActivitySegmentV(segments)).unwrap();
+        self.telem_data.set("analysis_start".to_string(), TelemData::F32(range.0)).unwrap();
+        self.telem_data.set("analysis_end".to_string(), TelemData::F32(range.1)).unwrap();
+        self.telem_data.set("annotations".to_string(), TelemData::Annotations(self.annotations.clone())).unwrap();
+
+        let rear_sus_raw_f32 = rear_sus_data_f32.clone();
+        let front_sus_raw_f32 = front_sus_data_f32.clone();
+        rear_sus_data_f32 = self.filter_channel("RS".to_string(), "rear_suspension_filtered".to_string(), rear_sus_data_f32, rs_polling_rate);
+        front_sus_data_f32 = self.filter_channel("FS".to_string(), "front_suspension_filtered".to_string(), front_sus_data_f32, fs_polling_rate);
+
+        let (rear_histogram_range, front_histogram_range) = (self.rear_histogram.range(rear_travel_max), self.front_histogram.range(front_travel_max));
+        self.telem_data.set_count("rear_suspension_counts".to_string(), &rear_sus_data_f32[rear_range.clone()].to_vec(), self.rear_histogram.bins, rear_histogram_range.0, rear_histogram_range.1, false).unwrap();
+        self.telem_data.set_count("front_suspension_counts".to_string(), &front_sus_data_f32[front_range.clone()].to_vec(), self.front_histogram.bins, front_histogram_range.0, front_histogram_range.1, false).unwrap();
+        self.telem_data.set_travel_stats("rear_travel_stats".to_string(), &rear_sus_data_f32[rear_range.clone()], rs_polling_rate, rear_travel_max).unwrap();
+        self.telem_data.set_travel_stats("front_travel_stats".to_string(), &front_sus_data_f32[front_range.clone()], fs_polling_rate, front_travel_max).unwrap();
+
+        self.telem_data.set_strokes("rear".to_string(), &rear_sus_data_f32, rear_range.clone(), rs_polling_rate, &rear_stroke_detector).unwrap();
+        self.telem_data.set_strokes("front".to_string(), &front_sus_data_f32, front_range.clone(), fs_polling_rate, &front_stroke_detector).unwrap();
+        for direction in ["compression", "rebound"] {
+            let rear_strokes = self.telem_data.get_f64pv("rear_".to_string() + direction);
+            let front_strokes = self.telem_data.get_f64pv("front_".to_string() + direction);
+            if let Some(coefficient) = balance::balance_coefficient(rear_strokes, front_strokes) {
+                self.telem_data.set(direction.to_string() + "_balance", TelemData::F32(coefficient)).unwrap();
+            }
+        }
+        
+        let rear_line_manager = self.channel_line(&rear_sus_raw_f32, rs_polling_rate);
+        let front_line_manager = self.channel_line(&front_sus_raw_f32, fs_polling_rate);
         
         self.telem_data.set("rear_suspension_line".to_string(), TelemData::LineManager(rear_line_manager)).unwrap();
         self.telem_data.set("front_suspension_line".to_string(), TelemData::LineManager(front_line_manager)).unwrap();
 
 
-        let suspension_graph = SuspensionGraph::new("rear_suspension_line".to_string(), "front_suspension_line".to_string());
-        let mut rear_histogram = BarPoints::new("rear_suspension_counts".to_string(),Color32::RED);
-        let mut front_histogram = BarPoints::new("front_suspension_counts".to_string(),Color32::LIGHT_BLUE);
-        rear_histogram.set_dims(500.0, 500.0);
-        front_histogram.set_dims(500.0, 500.0);
+        let mut suspension_graph = SuspensionGraph::new("rear_suspension_line".to_string(), "front_suspension_line".to_string());
+        suspension_graph.set_filtered_lines("rear_suspension_filtered_line".to_string(), "front_suspension_filtered_line".to_string());
+        suspension_graph.set_travel_range(f32::max(rear_travel_max, front_travel_max) as f64, self.travel_unit_name().to_string());
+        suspension_graph.set_x_axis(self.shown_x_axis());
+        let mut histogram = BarPoints::new("suspension_counts".to_string());
+        histogram.add_series("rear_suspension_counts".to_string(), "Rear".to_string(), Color32::RED, rear_histogram_range, self.rear_histogram.normalisation.scale(rear_range.len(), rs_polling_rate));
+        histogram.add_series("front_suspension_counts".to_string(), "Front".to_string(), Color32::LIGHT_BLUE, front_histogram_range, self.front_histogram.normalisation.scale(front_range.len(), fs_polling_rate));
+        let y_label = if self.rear_histogram.normalisation == self.front_histogram.normalisation {
+            self.rear_histogram.normalisation.name().to_string()
+        } else {
+            format!("rear: {}, front: {}", self.rear_histogram.normalisation.name(), self.front_histogram.normalisation.name())
+        };
+        histogram.set_labels(format!("travel ({})", self.travel_unit_name()), y_label);
+        histogram.set_dims(1000.0, 500.0);
 
         let disp_vel_rebound = DispVelGraph::new("rebound".to_string(),"front_rebound".to_string(),"rear_rebound".to_string());
         let disp_vel_compression = DispVelGraph::new("compression".to_string(),"front_compression".to_string(),"rear_compression".to_string());
 
         self.sus_view = View::new();
         self.sus_view.add_graph(1, Box::new(suspension_graph));
-        self.sus_view.add_graph(2, Box::new(rear_histogram));
-        self.sus_view.add_graph(2, Box::new(front_histogram));
+        self.sus_view.add_graph(2, Box::new(histogram));
         self.sus_view.add_graph(3,Box::new(disp_vel_rebound));
         self.sus_view.add_graph(4,Box::new(disp_vel_compression));
+        self.sus_view.add_graph(5, Box::new(BalanceGraph::new("travel_balance".to_string())));
+        let spectrum_label = if self.spectrum_welch { "PSD" } else { "amplitude" };
+        self.sus_view.add_graph(6, Box::new(SpectrumGraph::new("spectrum".to_string(), "spectrogram".to_string(), spectrum_label.to_string(), !self.spectrum_welch)));
+        self.sus_view.add_graph(7, Box::new(DamperGraph::new()));
+        self.sus_view.add_graph(8, Box::new(SpringGraph::new("rear".to_string(), Color32::RED)));
+        self.sus_view.add_graph(8, Box::new(SpringGraph::new("front".to_string(), Color32::LIGHT_BLUE)));
+        let mut orientation_graph = TimeGraph::new("orientation".to_string(), "angle (°)".to_string());
+        orientation_graph.add_line("pitch_line".to_string(), "Pitch".to_string(), Color32::GOLD);
+        orientation_graph.add_line("roll_line".to_string(), "Roll".to_string(), Color32::LIGHT_GREEN);
+        orientation_graph.set_x_axis(self.shown_x_axis());
+        self.sus_view.add_graph(9, Box::new(orientation_graph));
+        for (row, (field, name, y_label)) in [(10, ("speed", "Speed", "speed (km/h)")), (11, ("distance", "Distance", "distance (m)"))] {
+            let mut graph = TimeGraph::new(field.to_string(), y_label.to_string());
+            graph.add_line(field.to_string() + "_line", name.to_string(), Color32::LIGHT_YELLOW);
+            graph.set_x_axis(self.shown_x_axis());
+            self.sus_view.add_graph(row, Box::new(graph));
+        }
+        if let Some(orientation) = orientation {
+            self.set_orientation(orientation);
+        }
+        self.set_spectrum(&rear_sus_raw_f32, &front_sus_raw_f32, rs_polling_rate, fs_polling_rate, range);
+
+
+        // the thresholds and sag are in % of travel so are meaningless for unmapped data
+        if !self.show_unmapped_data {
+            self.set_sag(&rear_percent, &front_percent, rs_polling_rate, fs_polling_rate, range, (rear_static_sag, front_static_sag));
+            let balance_curve = balance::percentile_curve(&rear_percent[rear_range.clone()], &front_percent[front_range.clone()], 100);
+            self.telem_data.set("travel_balance".to_string(), TelemData::F32PV(balance_curve)).unwrap();
+            if self.loader.imu.is_present() {
+                let mut jumps = self.config.jump_detector.detect(&self.loader.imu, (&rear_percent, rs_polling_rate), (&front_percent, fs_polling_rate));
+                jumps.retain(|j| j.take_off >= range.0 && j.take_off < range.1);
+                self.telem_data.set("jumps".to_string(), TelemData::JumpV(jumps)).unwrap();
+            }
+            self.set_braking_events(&rear_percent, &front_percent, rs_polling_rate, fs_polling_rate, range);
+            self.set_pack_downs("rear", &rear_percent, range);
+            self.set_pack_downs("front", &front_percent, range);
+            self.set_spring("rear", bike.rear_spring, rear_range.clone());
+            self.set_spring("front", bike.front_spring, front_range.clone());
+            self.set_damper_curve("rear", bike.rear_spring, rs_polling_rate);
+            self.set_damper_curve("front", bike.front_spring, fs_polling_rate);
+            self.count_travel_events("rear", &rear_sus_data_f32, rs_polling_rate, rear_thresholds, range);
+            self.count_travel_events("front", &front_sus_data_f32, fs_polling_rate, front_thresholds, range);
+        }
+    }
+
+    /// Measures the sag of both ends
+    ///
+    /// # Arguments
+    ///
+    /// `rear_percent`: Mapped rear travel in %
+    /// `front_percent`: Mapped front travel in %
+    /// `rs_polling_rate`: Polling rate of the rear channel
+    /// `fs_polling_rate`: Polling rate of the front channel
+    /// `range`: (start, end) in seconds of the portion being analysed
+    /// `calibrated`: (rear, front) static sag from the calibration wizard, used when the run has no time sat still
+    /// before the riding
+    fn set_sag(&mut self, rear_percent: &[f32], front_percent: &[f32], rs_polling_rate: f32, fs_polling_rate: f32, range: (f32, f32), calibrated: (Option<CalibratedSag>, Option<CalibratedSag>)) {
+        let wheel_travel_mm = |end: &str| match self.telem_data.get(end.to_string() + "_wheel_travel_mm") {
+            Ok(TelemData::F32V(mm)) => Some(mm.as_slice()),
+            _ => None,
+        };
+        let sag = SagMetrics {
+            front: EndSag::measure(front_percent, wheel_travel_mm("front"), fs_polling_rate, range).or_calibrated(calibrated.1),
+            rear: EndSag::measure(rear_percent, wheel_travel_mm("rear"), rs_polling_rate, range).or_calibrated(calibrated.0),
+        };
+
+        self.telem_data.set("sag".to_string(), TelemData::Sag(sag)).unwrap();
+    }
+
+    /// Finds the braking events in the brake channels, if the run has any
+    ///
+    /// # Arguments
+    ///
+    /// `rear_percent`: Mapped rear travel in %
+    /// `front_percent`: Mapped front travel in %
+    /// `rs_polling_rate`: Polling rate of the rear channel
+    /// `fs_polling_rate`: Polling rate of the front channel
+    /// `range`: (start, end) in seconds, only events starting in it are kept
+    fn set_braking_events(&mut self, rear_percent: &[f32], front_percent: &[f32], rs_polling_rate: f32, fs_polling_rate: f32, range: (f32, f32)) {
+        let brake_channel = |tag: &str| {
+            self.loader.raw_pot_datas.get(tag).map(|p| (p.data.iter().map(|d| *d as f32).collect::<Vec<f32>>(), p.polling_rate as f32))
+        };
+        let (front_brake, rear_brake) = (brake_channel("FB"), brake_channel("RB"));
+        if front_brake.is_none() && rear_brake.is_none() {
+            return;
+        }
+
+        let mut events = self.config.brake_detector.detect(
+            front_brake.as_ref().map(|(data, rate)| (data.as_slice(), *rate)),
+            rear_brake.as_ref().map(|(data, rate)| (data.as_slice(), *rate)),
+            (front_percent, fs_polling_rate),
+            (rear_percent, rs_polling_rate),
+        );
+        events.retain(|e| e.start >= range.0 && e.start < range.1);
+
+        self.telem_data.set("braking_events".to_string(), TelemData::BrakingEventV(events)).unwrap();
+    }
 
+    /// Finds where one end packed down over the analysis range and scores it
+    ///
+    /// # Arguments
+    ///
+    /// `end`: "rear" or "front", the pack downs are stored as `end` + "_pack_downs" and the score as
+    /// `end` + "_pack_down_score"
+    /// `percent`: Mapped travel in %
+    /// `range`: (start, end) in seconds of the portion being analysed, the strokes are already limited to it
+    fn set_pack_downs(&mut self, end: &str, percent: &[f32], range: (f32, f32)) {
+        let pack_downs = match self.telem_data.get_strokes(end.to_string() + "_strokes") {
+            Some(strokes) => self.config.pack_down_detector.detect(percent, strokes),
+            None => return,
+        };
 
-        self.telem_data.set("front_dyn_sag".to_string(), TelemData::F32(self.telem_data.data_average_raw(&front_sus_data_f32))).unwrap();
-        self.telem_data.set("rear_dyn_sag".to_string(), TelemData::F32(self.telem_data.data_average_raw(&rear_sus_data_f32))).unwrap();
-        self.count_bottom_outs();
+        self.telem_data.set(end.to_string() + "_pack_down_score", TelemData::F32(packdown::score(&pack_downs, range.1 - range.0))).unwrap();
+        self.telem_data.set(end.to_string() + "_pack_downs", TelemData::PackDownV(pack_downs)).unwrap();
+    }
+
+    /// Stores the spring curve of one end with a histogram of the shock stroke over it and the force used in the
+    /// deepest compressions, if the spring and shock stroke in mm are known
+    ///
+    /// # Arguments
+    ///
+    /// `end`: "rear" or "front", the fields are stored as `end` + "_spring_curve", `end` + "_spring_counts",
+    /// `end` + "_peak_force" and `end` + "_force_usage"
+    /// `spring`: The spring of the end
+    /// `range`: The samples being analysed
+    fn set_spring(&mut self, end: &str, spring: Option<SpringModel>, range: Range<usize>) {
+        let (shock_mm, full_stroke, spring) = match (self.telem_data.get(end.to_string() + "_shock_stroke_mm"), self.telem_data.get_f32_err(end.to_string() + "_full_stroke_mm"), spring) {
+            (Ok(TelemData::F32V(mm)), Some(full_stroke), Some(spring)) => (mm.clone(), full_stroke, spring),
+            _ => return,
+        };
+
+        // a compression stroke ends at its deepest point
+        let peaks: Vec<f32> = self.telem_data.get_strokes(end.to_string() + "_strokes").map_or(Vec::new(), |strokes| {
+            strokes.iter().filter(|s| s.direction == StrokeDirection::Compression).map(|s| shock_mm[s.end_index]).collect()
+        });
+        if let Some((force, usage)) = spring::force_usage(&spring, &peaks, full_stroke) {
+            self.telem_data.set(end.to_string() + "_peak_force", TelemData::F32(force)).unwrap();
+            self.telem_data.set(end.to_string() + "_force_usage", TelemData::F32(usage)).unwrap();
+        }
+
+        self.telem_data.set(end.to_string() + "_spring_curve", TelemData::F32PV(spring.curve(full_stroke, SPRING_CURVE_STEPS))).unwrap();
+        self.telem_data.set_count(end.to_string() + "_spring_counts", &shock_mm[range].to_vec(), SPRING_HISTOGRAM_BINS, 0.0, full_stroke as f64, false).unwrap();
+    }
+
+    /// Estimates the damper curve of one end from its strokes, if the spring and shock stroke in mm are known
+    ///
+    /// Needs the sag to have been measured, the static sag is used when the run starts sat still and the dynamic sag
+    /// otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// `end`: "rear" or "front", the curves are stored as `end` + "_damper_compression" and `end` + "_damper_rebound"
+    /// `spring`: The spring of the end
+    /// `polling_rate`: Polling rate of the channel
+    fn set_damper_curve(&mut self, end: &str, spring: Option<SpringModel>, polling_rate: f32) {
+        let (shock_mm, spring) = match (self.telem_data.get(end.to_string() + "_shock_stroke_mm"), spring) {
+            (Ok(TelemData::F32V(mm)), Some(spring)) => (mm, spring),
+            _ => return,
+        };
+        let (sag, full_stroke) = match (self.telem_data.get_sag("sag".to_string()), self.telem_data.get_f32_err(end.to_string() + "_full_stroke_mm")) {
+            (Some(sag), Some(full_stroke)) => (if end == "rear" { sag.rear } else { sag.front }, full_stroke),
+            _ => return,
+        };
+        let sag_mm = sag.static_percent.unwrap_or(sag.dynamic_percent) / config_info::MAPPED_MAX * full_stroke;
+        let strokes = match self.telem_data.get_strokes(end.to_string() + "_strokes") {
+            Some(strokes) => strokes,
+            None => return,
+        };
+
+        let curve = damper::estimate(shock_mm, polling_rate, strokes, &spring, sag_mm);
+        self.telem_data.set(end.to_string() + "_damper_compression", TelemData::F32PV(curve.compression)).unwrap();
+        self.telem_data.set(end.to_string() + "_damper_rebound", TelemData::F32PV(curve.rebound)).unwrap();
+    }
+
+    /// Stores the pitch and roll from the IMU as channels with lines to plot
+    fn set_orientation(&mut self, orientation: Orientation) {
+        let polling_rate = self.loader.imu.polling_rate as f32;
+        for (field, angles) in [("pitch", orientation.pitch), ("roll", orientation.roll)] {
+            let line_manager = self.channel_line(&angles, polling_rate);
+            self.telem_data.set(field.to_string() + "_line", TelemData::LineManager(line_manager)).unwrap();
+            self.telem_data.set(field.to_string(), TelemData::F32V(angles)).unwrap();
+        }
+    }
+
+    /// Finds the distance along the run from the speed, measured by the wheel speed sensor if the run has one and
+    /// otherwise estimated from the IMU
+    ///
+    /// Only the measured speed is stored as speed and distance channels, the IMU estimate is too rough to read from.
+    ///
+    /// # Arguments
+    ///
+    /// `orientation`: The orientation of the bike, None if the run has no IMU data
+    /// `segments`: The riding and idle segments of the run
+    /// `range`: (start, end) in seconds of the portion being analysed
+    fn set_distance(&mut self, orientation: Option<&Orientation>, segments: &[ActivitySegment], range: (f32, f32)) {
+        self.distance_source = None;
+        let wheel_speed = self.loader.get_wheel_speed_data().map(|(pot, kind)| {
+            let data: Vec<f32> = pot.data.iter().map(|d| *d as f32).collect();
+            let polling_rate = pot.polling_rate as f32;
+            (self.current_bike().wheel_sensor.speed(&data, kind, polling_rate), polling_rate)
+        });
+        let (speed, polling_rate, measured) = match (wheel_speed, orientation) {
+            (Some((speed, polling_rate)), _) => (speed, polling_rate, true),
+            (None, Some(orientation)) => {
+                let polling_rate = self.loader.imu.polling_rate as f32;
+                (distance::imu_speed(&orientation.forward_accel, polling_rate, segments), polling_rate, false)
+            }
+            (None, None) => return,
+        };
+
+        let distance_map = DistanceMap::from_speed(&speed, polling_rate);
+        let distance = distance_map.distance.clone();
+        self.telem_data.set("distance_map".to_string(), TelemData::DistanceMap(distance_map)).unwrap();
+        self.distance_source = Some(if measured { "wheel speed" } else { "IMU estimate" });
+        if !measured {
+            return;
+        }
+
+        let analysed = &speed[sample_range(range, polling_rate, speed.len())];
+        if !analysed.is_empty() {
+            let max_speed = analysed.iter().copied().fold(0.0, f32::max);
+            let mean_speed = analysed.iter().sum::<f32>() / analysed.len() as f32;
+            self.telem_data.set("max_speed".to_string(), TelemData::F32(max_speed)).unwrap();
+            self.telem_data.set("mean_speed".to_string(), TelemData::F32(mean_speed)).unwrap();
+        }
+
+        let speed_kmh: Vec<f32> = speed.iter().map(|s| s * wheel_speed::MS_TO_KMH).collect();
+        let speed_line = self.channel_line(&speed_kmh, polling_rate);
+        let distance_line = self.channel_line(&distance, polling_rate);
+        self.telem_data.set("speed_line".to_string(), TelemData::LineManager(speed_line)).unwrap();
+        self.telem_data.set("distance_line".to_string(), TelemData::LineManager(distance_line)).unwrap();
+        self.telem_data.set("speed".to_string(), TelemData::F32V(speed)).unwrap();
+        self.telem_data.set("distance".to_string(), TelemData::F32V(distance)).unwrap();
+    }
+
+    /// Line of a channel against the shown x axis
+    ///
+    /// # Arguments
+    ///
+    /// `data`: The samples of the channel
+    /// `polling_rate`: The polling rate of the channel
+    fn channel_line(&self, data: &[f32], polling_rate: f32) -> LineManager {
+        let distance_map = match self.shown_x_axis() {
+            XAxis::Distance => self.telem_data.get_distance_map("distance_map".to_string()),
+            XAxis::Time => None,
+        };
+
+        match distance_map {
+            Some(distance_map) => {
+                let points = data.iter().enumerate()
+                    .map(|(i, d)| PlotPoint::new(distance_map.distance_at(i as f32 / polling_rate), *d))
+                    .collect();
+                LineManager::new_sorted(points, polling_rate as f64)
+            }
+            None => {
+                let points = data.iter().enumerate().map(|(i, d)| PlotPoint::new(i as f32 / polling_rate, *d)).collect();
+                LineManager::new(points, polling_rate as f64)
+            }
+        }
+    }
+
+    /// The x axis the time based graphs are plotted against, time when distance is picked but the run has none
+    fn shown_x_axis(&self) -> XAxis {
+        match self.distance_source {
+            Some(_) => self.x_axis,
+            None => XAxis::Time,
+        }
+    }
+
+    /// Computes the spectrum and spectrogram of the selected channel over the analysis range
+    ///
+    /// # Arguments
+    ///
+    /// `rear_travel`: Unfiltered rear travel in the shown unit, used for "RS"
+    /// `front_travel`: Unfiltered front travel in the shown unit, used for "FS"
+    /// `rs_polling_rate`: Polling rate of the rear channel
+    /// `fs_polling_rate`: Polling rate of the front channel
+    /// `range`: (start, end) in seconds of the portion being analysed
+    fn set_spectrum(&mut self, rear_travel: &[f32], front_travel: &[f32], rs_polling_rate: f32, fs_polling_rate: f32, range: (f32, f32)) {
+        let (data, polling_rate) = match self.spectrum_channel.as_str() {
+            "RS" => (rear_travel.to_vec(), rs_polling_rate),
+            "FS" => (front_travel.to_vec(), fs_polling_rate),
+            tag => match (self.loader.imu.channel(tag), self.loader.raw_pot_datas.get(tag)) {
+                (Some(imu), _) if self.loader.imu.is_present() => (imu, self.loader.imu.polling_rate as f32),
+                (_, Some(pot)) => (pot.data.iter().map(|d| *d as f32).collect(), pot.polling_rate as f32),
+                _ => return,
+            },
+        };
+
+        let samples = sample_range(range, polling_rate, data.len());
+        let start_time = samples.start as f32 / polling_rate;
+        let data = &data[samples];
+        let segment_len = self.spectrum_segment_len;
+        let spectrum = if self.spectrum_welch {
+            spectrum::welch_psd(data, polling_rate, segment_len, 0.5)
+        } else {
+            spectrum::magnitude_spectrum(data, polling_rate)
+        };
+
+        self.telem_data.set("spectrum".to_string(), TelemData::F32PV(spectrum)).unwrap();
+        self.telem_data.set("spectrogram".to_string(), TelemData::Spectrogram(spectrum::spectrogram(data, polling_rate, segment_len, 0.5, start_time))).unwrap();
+    }
+
+    /// Name of the unit the loaded travel is shown in
+    fn travel_unit_name(&self) -> &'static str {
+        self.shown_travel_unit.map_or("raw", |u| u.name())
+    }
+
+    /// Stores the shock stroke and wheel travel in mm for one end, if the remap info has the lengths needed
+    ///
+    /// # Arguments
+    ///
+    /// `end`: "rear" or "front", the fields are stored as `end` + "_shock_stroke_mm" and `end` + "_wheel_travel_mm"
+    /// `data`: The mapped travel in %
+    /// `remap_info`: The remap info for the channel
+    /// `leverage_curve`: The leverage curve to get the wheel travel from the shock stroke, None to convert linearly
+    fn set_mm_channels(&mut self, end: String, data: &[f32], remap_info: &SuspensionRemapInfo, leverage_curve: Option<&LeverageCurve>) {
+        if !remap_info.has_mm() {
+            return;
+        }
+
+        let shock_stroke = data.iter().map(|d| remap_info.remap_stroke_mm(*d)).collect();
+        let wheel_travel = data.iter().map(|d| leverage::remap_wheel_travel_mm(remap_info, leverage_curve, *d)).collect();
+
+        self.telem_data.set(end.clone() + "_shock_stroke_mm", TelemData::F32V(shock_stroke)).unwrap();
+        self.telem_data.set(end.clone() + "_full_stroke_mm", TelemData::F32(remap_info.stroke_mm)).unwrap();
+        self.telem_data.set(end + "_wheel_travel_mm", TelemData::F32V(wheel_travel)).unwrap();
+    }
+
+    /// Runs the configured filter (if any) for a channel, storing the filtered samples and line in telem_data
+    ///
+    /// # Arguments
+    ///
+    /// `channel`: The channel tag from the run file header, used to look up the filter in the config
+    /// `field`: The field to store the filtered samples under, the line is stored under `field` + "_line"
+    /// `data`: The samples to filter
+    /// `polling_rate`: The polling rate of the channel
+    ///
+    /// # Returns
+    ///
+    /// The filtered samples, or `data` unchanged if the channel has no filter
+    fn filter_channel(&mut self, channel: String, field: String, data: Vec<f32>, polling_rate: f32) -> Vec<f32> {
+        let filter = match self.config.get_channel_filter(channel) {
+            Some(f) => f,
+            None => return data,
+        };
+
+        let filtered = filter.apply(&data, polling_rate);
+        let line_manager = self.channel_line(&filtered, polling_rate);
+
+        self.telem_data.set(field.clone() + "_line", TelemData::LineManager(line_manager)).unwrap();
+        self.telem_data.set(field, TelemData::F32V(filtered.clone())).unwrap();
+
+        filtered
     }
 }
 
@@ -211,6 +750,9 @@ impl<'a> eframe::App for TelemApp<'a> {
                     if ui.button("Config").clicked() {
                         self.config_window.open = true;
                     }
+                    if ui.button("Calibration wizard").clicked() {
+                        self.calibration_wizard.open = true;
+                    }
                     if ui.button("Quit").clicked() {
                         //frame.close();
                     }
@@ -246,6 +788,9 @@ impl<'a> eframe::App for TelemApp<'a> {
                 }
                 if ui.button("Load").clicked() {
                     self.loader.load(self.path.to_string());
+                    let annotations = RunAnnotations::load(&self.path);
+                    self.sections_window.reset(annotations.as_ref().err().cloned());
+                    self.annotations = annotations.unwrap_or_default();
                     updated_data = true;  
                 }
             });
@@ -273,6 +818,41 @@ impl<'a> eframe::App for TelemApp<'a> {
                 curr_sus_remap_info = csri
             }
 
+            if let Some(remap_info) = self.config.sus_remap_info.get_mut(&self.current_remap_info_ref) {
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut remap_info.stroke_mm).clamp_range(0.0..=500.0).suffix(" mm").prefix("stroke: "));
+                    ui.add(egui::DragValue::new(&mut remap_info.wheel_travel_mm).clamp_range(0.0..=500.0).suffix(" mm").prefix("wheel travel: "));
+                });
+                ui.checkbox(&mut remap_info.calibrated_offset, "keep calibrated zero over the run file offset");
+                if ui.button("Calibration table").clicked() {
+                    self.calibration_window.open = true;
+                }
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("travel unit: ");
+                for unit in [TravelUnit::Percent, TravelUnit::Millimetres] {
+                    if ui.radio_value(&mut self.travel_unit, unit, unit.name()).clicked() {
+                        updated_data = true;
+                    }
+                }
+            });
+            if self.travel_unit == TravelUnit::Millimetres && self.shown_travel_unit == Some(TravelUnit::Percent) {
+                ui.label("stroke and wheel travel must be set for both ends to show mm");
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("plot against: ");
+                for x_axis in [XAxis::Time, XAxis::Distance] {
+                    if ui.radio_value(&mut self.x_axis, x_axis, x_axis.name()).clicked() {
+                        updated_data = true;
+                    }
+                }
+            });
+            if self.x_axis == XAxis::Distance && self.distance_source.is_none() && !self.loader.raw_pot_datas.is_empty() {
+                ui.label("the run needs IMU or wheel speed data to plot against distance");
+            }
+
             ui.horizontal(|ui| {
 
 
@@ -291,32 +871,419 @@ impl<'a> eframe::App for TelemApp<'a> {
 
 
 
+            ui.heading("Bike");
+
+            egui::ComboBox::new("bike_selector", "Select Bike")
+                .selected_text(self.current_bike_ref.clone())
+                .show_ui(ui, |ui| {
+                    for bike_key in self.config.bikes.keys() {
+                        ui.selectable_value(&mut self.current_bike_ref, bike_key.to_string(), bike_key);
+                    }
+                });
+
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.new_bike_name);
+                if ui.button("Add bike").clicked() && !self.new_bike_name.is_empty() {
+                    self.config.add_bike(self.new_bike_name.clone(), BikeConfig::default());
+                    self.current_bike_ref = std::mem::take(&mut self.new_bike_name);
+                }
+            });
+
+            if let Some(bike) = self.config.get_bike_mut(self.current_bike_ref.clone()) {
+                for (label, thresholds) in [("Front", &mut bike.front_thresholds), ("Rear", &mut bike.rear_thresholds)] {
+                    ui.label(label.to_string() + " thresholds (% travel)");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut thresholds.bottom_out).clamp_range(0.0..=100.0).speed(0.1).prefix("bottom out: "));
+                        ui.add(egui::DragValue::new(&mut thresholds.top_out).clamp_range(0.0..=100.0).speed(0.1).prefix("top out: "));
+                        ui.add(egui::DragValue::new(&mut thresholds.hysteresis).clamp_range(0.0..=50.0).speed(0.1).prefix("hysteresis: "));
+                    });
+                }
+
+                for (label, spring) in [("Front", &mut bike.front_spring), ("Rear", &mut bike.rear_spring)] {
+                    egui::ComboBox::new(label.to_string() + "_spring_selector", label.to_string() + " spring")
+                        .selected_text(spring.map_or("None", |s| s.name()))
+                        .show_ui(ui, |ui| {
+                            if ui.selectable_label(spring.is_none(), "None").clicked() {
+                                *spring = None;
+                            }
+                            for default_spring in SpringModel::defaults() {
+                                let selected = spring.map_or(false, |s| s.name() == default_spring.name());
+                                if ui.selectable_label(selected, default_spring.name()).clicked() && !selected {
+                                    *spring = Some(default_spring);
+                                }
+                            }
+                        });
+
+                    match spring {
+                        Some(SpringModel::Coil { rate, preload }) => {
+                            ui.horizontal(|ui| {
+                                ui.add(egui::DragValue::new(rate).clamp_range(0.0..=1000.0).speed(0.5).prefix("rate: ").suffix(" N/mm"));
+                                ui.add(egui::DragValue::new(preload).clamp_range(0.0..=50.0).speed(0.1).prefix("preload: ").suffix(" mm"));
+                            });
+                        }
+                        Some(SpringModel::Air { pressure, volume, piston_area, tokens, token_volume }) => {
+                            ui.horizontal(|ui| {
+                                ui.add(egui::DragValue::new(pressure).clamp_range(0.0..=500.0).prefix("pressure: ").suffix(" psi"));
+                                ui.add(egui::DragValue::new(volume).clamp_range(1.0..=2000.0).prefix("volume: ").suffix(" cm³"));
+                            });
+                            ui.horizontal(|ui| {
+                                ui.add(egui::DragValue::new(piston_area).clamp_range(1.0..=5000.0).prefix("piston area: ").suffix(" mm²"));
+                                ui.add(egui::DragValue::new(tokens).clamp_range(0..=10).prefix("tokens: "));
+                                ui.add(egui::DragValue::new(token_volume).clamp_range(0.0..=100.0).speed(0.1).prefix("token: ").suffix(" cm³"));
+                            });
+                        }
+                        None => {}
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    let sensor = &mut bike.wheel_sensor;
+                    ui.add(egui::DragValue::new(&mut sensor.circumference).clamp_range(100.0..=4000.0).prefix("wheel circumference: ").suffix(" mm"));
+                    ui.add(egui::DragValue::new(&mut sensor.pulses_per_revolution).clamp_range(1..=64).prefix("magnets: "));
+                });
+
+                ui.horizontal(|ui| {
+                    if ui.button("Apply bike settings").clicked() {
+                        updated_data = true;
+                    }
+                    if ui.button("Leverage curve").clicked() {
+                        self.leverage_window.open = true;
+                    }
+                });
+            }
+
+            ui.separator();
+
+            ui.heading("Filters");
+
+            for (channel, label) in [("RS", "Rear"), ("FS", "Front")] {
+                let mut filter = self.config.get_channel_filter(channel.to_string());
+
+                egui::ComboBox::new(channel.to_string() + "_filter_selector", label)
+                    .selected_text(filter.map_or("None", |f| f.name()))
+                    .show_ui(ui, |ui| {
+                        if ui.selectable_label(filter.is_none(), "None").clicked() {
+                            filter = None;
+                        }
+                        for default_filter in Filter::defaults() {
+                            let selected = filter.map_or(false, |f| f.name() == default_filter.name());
+                            if ui.selectable_label(selected, default_filter.name()).clicked() && !selected {
+                                filter = Some(default_filter);
+                            }
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    match &mut filter {
+                        Some(Filter::MovingAverage { window }) | Some(Filter::Median { window }) => {
+                            ui.add(egui::DragValue::new(window).clamp_range(1..=1000).prefix("window: "));
+                        }
+                        Some(Filter::Butterworth { cutoff, order }) => {
+                            ui.add(egui::DragValue::new(cutoff).clamp_range(0.1..=500.0).prefix("cutoff: ").suffix(" Hz"));
+                            ui.add(egui::DragValue::new(order).clamp_range(1..=8).prefix("order: "));
+                        }
+                        Some(Filter::SavitzkyGolay { window, poly_order }) => {
+                            ui.add(egui::DragValue::new(window).clamp_range(3..=1001).prefix("window: "));
+                            ui.add(egui::DragValue::new(poly_order).clamp_range(1..=6).prefix("order: "));
+                        }
+                        None => {}
+                    }
+                });
+
+                self.config.set_channel_filter(channel.to_string(), filter);
+            }
+
+            if ui.button("Apply filters").clicked() {
+                updated_data = true;
+            }
+
+            ui.separator();
+
+            ui.heading("Histograms");
+            for (label, settings) in [("Rear", &mut self.rear_histogram), ("Front", &mut self.front_histogram)] {
+                ui.label(label.to_string() + " (range in % travel)");
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut settings.bins).clamp_range(1..=200).prefix("bins: "));
+                    ui.add(egui::DragValue::new(&mut settings.min).clamp_range(0.0..=settings.max).speed(0.5).prefix("from: "));
+                    ui.add(egui::DragValue::new(&mut settings.max).clamp_range(settings.min..=100.0).speed(0.5).prefix("to: "));
+                });
+                ui.horizontal(|ui| {
+                    for normalisation in [Normalisation::Count, Normalisation::PercentTime, Normalisation::Seconds] {
+                        ui.radio_value(&mut settings.normalisation, normalisation, normalisation.name());
+                    }
+                });
+            }
+            if ui.button("Apply histograms").clicked() {
+                updated_data = true;
+            }
+
+            ui.separator();
+
+            ui.heading("Stroke Detection");
+            ui.label("hysteresis and min amplitude (% travel)");
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut self.config.stroke_detector.hysteresis).clamp_range(0.0..=100.0).speed(0.1).prefix("hysteresis: "));
+                ui.add(egui::DragValue::new(&mut self.config.stroke_detector.min_amplitude).clamp_range(0.0..=100.0).speed(0.1).prefix("min amplitude: "));
+            });
+            if ui.button("Detect strokes").clicked() {
+                updated_data = true;
+            }
+
+            ui.label("pack down");
+            ui.horizontal(|ui| {
+                let detector = &mut self.config.pack_down_detector;
+                ui.add(egui::DragValue::new(&mut detector.min_hits).clamp_range(2..=50).prefix("min hits: "));
+                ui.add(egui::DragValue::new(&mut detector.min_drift).clamp_range(0.0..=100.0).speed(0.1).prefix("min drift: ").suffix(" %"));
+            });
+            ui.horizontal(|ui| {
+                let detector = &mut self.config.pack_down_detector;
+                ui.add(egui::DragValue::new(&mut detector.tolerance).clamp_range(0.0..=50.0).speed(0.1).prefix("tolerance: ").suffix(" %"));
+                ui.add(egui::DragValue::new(&mut detector.max_gap).clamp_range(0.0..=10.0).speed(0.01).prefix("max gap: ").suffix(" s"));
+            });
+            if ui.button("Detect pack down").clicked() {
+                updated_data = true;
+            }
+
+            ui.separator();
+
+            ui.heading("Analysis Range");
+            let analysed = self.telem_data.get_f32_err("analysis_start".to_string()).zip(self.telem_data.get_f32_err("analysis_end".to_string()));
+            if let Some((start, end)) = analysed {
+                let source = match (&self.analysed_section, self.selected_range) {
+                    (Some(name), _) if self.annotations.find_section(name).is_some() => "section ".to_string() + name,
+                    (_, Some(_)) => "selected".to_string(),
+                    _ => "riding portion".to_string(),
+                };
+                ui.label(format!("analysing {:.1} s - {:.1} s ({})", start, end, source));
+            }
+            ui.label("right drag on the suspension graph to select a range");
+
+            let mut detect_riding = self.selected_range.is_none();
+            if ui.checkbox(&mut detect_riding, "trim to riding portion").changed() {
+                self.selected_range = if detect_riding { None } else { Some(analysed.unwrap_or((0.0, 0.0))) };
+                self.analysed_section = None;
+                updated_data = true;
+            }
+            match &mut self.selected_range {
+                Some((start, end)) => {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(start).clamp_range(0.0..=f32::MAX).speed(0.1).prefix("start: ").suffix(" s"));
+                        ui.add(egui::DragValue::new(end).clamp_range(0.0..=f32::MAX).speed(0.1).prefix("end: ").suffix(" s"));
+                    });
+                }
+                None => {
+                    let detector = &mut self.config.activity_detector;
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut detector.window).clamp_range(0.1..=10.0).speed(0.1).prefix("window: ").suffix(" s"));
+                        ui.add(egui::DragValue::new(&mut detector.min_idle).clamp_range(0.0..=600.0).speed(0.1).prefix("min idle: ").suffix(" s"));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut detector.threshold).clamp_range(0.0..=50.0).speed(0.1).prefix("travel: ").suffix(" %"));
+                        ui.add(egui::DragValue::new(&mut detector.accel_threshold).clamp_range(0.0..=50.0).speed(0.1).prefix("accel: ").suffix(" m/s²"));
+                    });
+                }
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Apply range").clicked() {
+                    self.analysed_section = None;
+                    updated_data = true;
+                }
+                let range_picked = self.selected_range.is_some() || self.analysed_section.is_some();
+                if ui.add_enabled(range_picked, egui::Button::new("Reset range")).clicked() {
+                    self.selected_range = None;
+                    self.analysed_section = None;
+                    updated_data = true;
+                }
+                if ui.button("Sections").clicked() {
+                    self.sections_window.open = true;
+                }
+            });
+
+            ui.separator();
+
+            ui.heading("Orientation");
+            ui.horizontal(|ui| {
+                let filter = &mut self.config.orientation_filter;
+                ui.add(egui::DragValue::new(&mut filter.time_constant).clamp_range(0.01..=10.0).speed(0.01).prefix("time constant: ").suffix(" s"));
+                ui.add(egui::DragValue::new(&mut filter.accel_tolerance).clamp_range(0.0..=2.0).speed(0.01).prefix("accel tolerance: ").suffix(" g"));
+            });
+            ui.checkbox(&mut self.config.orientation_filter.level_at_start, "level at start of run");
+            if ui.button("Apply orientation").clicked() {
+                updated_data = true;
+            }
+
+            ui.separator();
+
+            ui.heading("Spectrum");
+            let mut channels: Vec<String> = vec!["RS".to_string(), "FS".to_string()];
+            let mut tags: Vec<&String> = self.loader.raw_pot_datas.keys().filter(|t| *t != "RS" && *t != "FS").collect();
+            tags.sort();
+            channels.extend(tags.into_iter().cloned());
+            if self.loader.imu.is_present() {
+                channels.extend(ImuData::CHANNEL_NAMES.iter().map(|n| n.to_string()));
+            }
+            egui::ComboBox::new("spectrum_channel_selector", "channel")
+                .selected_text(self.spectrum_channel.clone())
+                .show_ui(ui, |ui| {
+                    for channel in channels {
+                        ui.selectable_value(&mut self.spectrum_channel, channel.clone(), channel);
+                    }
+                });
+            egui::ComboBox::new("spectrum_segment_selector", "segment length")
+                .selected_text(self.spectrum_segment_len.to_string())
+                .show_ui(ui, |ui| {
+                    for len in [256, 512, 1024, 2048, 4096] {
+                        ui.selectable_value(&mut self.spectrum_segment_len, len, len.to_string());
+                    }
+                });
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.spectrum_welch, true, "Welch PSD");
+                ui.radio_value(&mut self.spectrum_welch, false, "FFT magnitude");
+            });
+            if ui.button("Compute spectrum").clicked() {
+                updated_data = true;
+            }
+
+            ui.separator();
+
             ui.heading("Suspension information");
             ui.heading("Suspension Data");
-            ui.label("dynamic sag");
+            ui.label("sag");
 
-            let (f_average,r_average);
+            let sag = self.telem_data.get_sag("sag".to_string()).copied();
+            egui::Grid::new("sag_grid").show(ui, |ui| {
+                ui.label("");
+                ui.label("static");
+                ui.label("dynamic");
+                ui.end_row();
 
-            match  self.telem_data.get_f32_err("front_dyn_sag".to_string()){
-                Some(val) => f_average = val,
-                None => f_average = 1000.0,
-            }  
-            match  self.telem_data.get_f32_err("rear_dyn_sag".to_string()){
-                Some(val) => r_average = val,
-                None => r_average = 1000.0,
+                for (label, end_sag) in [("front", sag.map(|s| s.front)), ("rear", sag.map(|s| s.rear))] {
+                    ui.label(label);
+                    ui.label(format_sag(end_sag.and_then(|s| s.static_percent), end_sag.and_then(|s| s.static_mm)));
+                    ui.label(format_sag(end_sag.map(|s| s.dynamic_percent), end_sag.and_then(|s| s.dynamic_mm)));
+                    ui.end_row();
+                }
+            });
+            match sag {
+                Some(sag) => {
+                    ui.label(format!("balance (front - rear): {:+.1} %", sag.balance()));
+                }
+                None => {
+                    ui.label("balance (front - rear): -");
+                }
             }
 
-            ui.horizontal(|ui|{
-                ui.label("front: ");
-                ui.label(f_average.to_string());
-                ui.label("    rear: ");
-                ui.label(r_average.to_string());
+            egui::Grid::new("travel_summary_grid").show(ui, |ui| {
+                for heading in ["", "median", "P95", "max", "time > 95 %"] {
+                    ui.label(heading);
+                }
+                ui.end_row();
+
+                for end in ["front", "rear"] {
+                    if let Some(stats) = self.telem_data.get_travel_stats(end.to_string() + "_travel_stats") {
+                        ui.label(end);
+                        ui.label(format!("{:.1}", stats.median));
+                        ui.label(format!("{:.1}", stats.p95));
+                        ui.label(format!("{:.1}", stats.max));
+                        ui.label(format!("{:.2} s", stats.time_above_95));
+                        ui.end_row();
+                    }
+                }
+            });
+
+            if let (Some(distance_map), Some(source)) = (self.telem_data.get_distance_map("distance_map".to_string()), self.distance_source) {
+                ui.label(format!("distance: {:.0} m ({})", distance_map.total(), source));
+            }
+            if let (Some(max_speed), Some(mean_speed)) = (self.telem_data.get_f32_err("max_speed".to_string()), self.telem_data.get_f32_err("mean_speed".to_string())) {
+                ui.label(format!("speed: max {:.1} km/h, mean {:.1} km/h", max_speed * wheel_speed::MS_TO_KMH, mean_speed * wheel_speed::MS_TO_KMH));
+            }
+
+            ui.label("bottom outs / top outs");
+            for (end, label) in [("front", "front: "), ("rear", "rear: ")] {
+                let events = match self.telem_data.get_travel_events(end.to_string() + "_travel_events") {
+                    Some(events) => events,
+                    None => continue,
+                };
+                let bottom_outs: Vec<_> = events.iter().filter(|e| e.kind == TravelEventKind::BottomOut).collect();
+                let top_outs: Vec<_> = events.iter().filter(|e| e.kind == TravelEventKind::TopOut).collect();
+
+                egui::CollapsingHeader::new(format!("{}{} / {}", label, bottom_outs.len(), top_outs.len()))
+                    .id_source(end.to_string() + "_travel_events")
+                    .show(ui, |ui| {
+                        for (kind_label, kind_events) in [("bottom out", &bottom_outs), ("top out", &top_outs)] {
+                            for event in kind_events {
+                                if ui.link(format!("{:.3} s {} ({:.1} {})", event.peak_time, kind_label, event.peak, self.travel_unit_name())).clicked() {
+                                    SuspensionGraph::request_zoom(ctx, event.start as f64 - 0.5, event.end as f64 + 0.5);
+                                }
+                            }
+                        }
+                    });
+            }
+
+            for end in ["front", "rear"] {
+                if let (Some(pack_downs), Some(score)) = (self.telem_data.get_pack_downs(end.to_string() + "_pack_downs"), self.telem_data.get_f32_err(end.to_string() + "_pack_down_score")) {
+                    ui.label(format!("{} pack down: {} runs, score {:.1} %/min", end, pack_downs.len(), score));
+                }
+            }
+            for end in ["front", "rear"] {
+                let usage = self.telem_data.get_f32_err(end.to_string() + "_peak_force").zip(self.telem_data.get_f32_err(end.to_string() + "_force_usage"));
+                if let Some((force, usage)) = usage {
+                    ui.label(format!("{} spring force used: {:.0} N ({:.0} % of the force at full stroke)", end, force, usage));
+                }
+            }
+
+            if let Some(jumps) = self.telem_data.get_jumps("jumps".to_string()) {
+                let longest = jumps.iter().map(|j| j.air_time).fold(0.0, f32::max);
+                ui.label(format!("jumps: {}    longest air time: {:.2} s", jumps.len(), longest));
+            }
+            if let Some(events) = self.telem_data.get_braking_events("braking_events".to_string()) {
+                let mean_dive = events.iter().map(|e| e.fork_dive).sum::<f32>() / usize::max(events.len(), 1) as f32;
+                ui.label(format!("braking events: {}    mean fork dive: {:.1} %", events.len(), mean_dive));
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Stroke table").clicked() {
+                    self.stroke_table.open = true;
+                }
+                if ui.button("Jump table").clicked() {
+                    self.jump_table.open = true;
+                }
+                if ui.button("Braking table").clicked() {
+                    self.braking_table.open = true;
+                }
+                if ui.button("Travel statistics").clicked() {
+                    self.travel_stats_window.open = true;
+                }
             });
 
 
         });
 
-        if updated_data {
+        if self.leverage_window.update(ctx, self.config.get_bike_mut(self.current_bike_ref.clone())) {
+            updated_data = true;
+        }
+        if self.calibration_window.update(ctx, &self.current_remap_info_ref, &mut self.config.calibrations) {
+            updated_data = true;
+        }
+        if self.calibration_wizard.update(ctx, &mut self.config) {
+            updated_data = true;
+        }
+
+        let analysis_range = self.telem_data.get_f32_err("analysis_start".to_string()).zip(self.telem_data.get_f32_err("analysis_end".to_string()));
+        if self.sections_window.update(ctx, &mut self.annotations, &self.path, &mut self.analysed_section, analysis_range) {
+            updated_data = true;
+        }
+
+        if let Some((start, end)) = SuspensionGraph::take_selection(ctx) {
+            // a click without a drag gives an empty range, which would leave nothing to analyse
+            if end > start {
+                self.selected_range = Some((start as f32, end as f32));
+                self.analysed_section = None;
+                updated_data = true;
+            }
+        }
+
+        if updated_data && !self.loader.raw_pot_datas.is_empty() {
             self.reset_data();
         }
 
@@ -338,6 +1305,13 @@ impl<'a> eframe::App for TelemApp<'a> {
         });
 
         self.config_window.update(ctx);
+        self.stroke_table.update(ctx, &self.telem_data);
+        self.travel_stats_window.update(ctx, &self.telem_data, self.travel_unit_name(), &self.path);
+        let jumps_changed = self.jump_table.update(ctx, &self.telem_data, &mut self.config.jump_detector);
+        let braking_changed = self.braking_table.update(ctx, &self.telem_data, &mut self.config.brake_detector);
+        if (jumps_changed || braking_changed) && !self.loader.raw_pot_datas.is_empty() {
+            self.reset_data();
+        }
 
         if false {
             egui::Window::new("Window").show(ctx, |ui| {
@@ -349,3 +1323,12 @@ impl<'a> eframe::App for TelemApp<'a> {
         }
     }
 }
+
+/// Sag as "% (mm)", "-" if it could not be measured
+fn format_sag(percent: Option<f32>, mm: Option<f32>) -> String {
+    match (percent, mm) {
+        (Some(p), Some(mm)) => format!("{:.1} % ({:.0} mm)", p, mm),
+        (Some(p), None) => format!("{:.1} %", p),
+        (None, _) => "-".to_string(),
+    }
+}
@@ -0,0 +1,68 @@
+/// Fewest strokes per end needed to fit a velocity trend
+const MIN_STROKES: usize = 3;
+
+/// Front travel percentiles against the matching rear travel percentiles
+///
+/// A balanced bike uses the same share of its travel at both ends, so the curve follows the line y = x.
+///
+/// # Arguments
+///
+/// `rear`: The rear travel in %
+/// `front`: The front travel in %
+/// `steps`: The number of steps between the 0th and 100th percentiles
+///
+/// # Returns
+///
+/// (rear percentile, front percentile) pairs, empty if either end has no data
+pub fn percentile_curve(rear: &[f32], front: &[f32], steps: usize) -> Vec<(f32, f32)> {
+    if rear.is_empty() || front.is_empty() || steps == 0 {
+        return Vec::new();
+    }
+
+    let sorted = |data: &[f32]| {
+        let mut sorted = data.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        sorted
+    };
+    let (rear, front) = (sorted(rear), sorted(front));
+    let percentile = |data: &[f32], fraction: f32| data[((data.len() - 1) as f32 * fraction).round() as usize];
+
+    (0..=steps)
+        .map(|i| {
+            let fraction = i as f32 / steps as f32;
+            (percentile(&rear, fraction), percentile(&front, fraction))
+        })
+        .collect()
+}
+
+/// Slope of a line through the origin fitted to (amplitude, peak velocity) pairs of strokes
+///
+/// # Returns
+///
+/// The peak velocity per unit of amplitude, None if there are too few strokes to fit
+pub fn velocity_trend(strokes: &[(f32, f32)]) -> Option<f32> {
+    let amplitude_sq: f32 = strokes.iter().map(|(a, _)| a * a).sum();
+    if strokes.len() < MIN_STROKES || amplitude_sq == 0.0 {
+        return None;
+    }
+
+    Some(strokes.iter().map(|(a, v)| a * v).sum::<f32>() / amplitude_sq)
+}
+
+/// Front velocity trend divided by the rear velocity trend
+///
+/// The trends are velocity per unit of amplitude so the result is the same in % and mm. 1.0 is balanced, above 1.0
+/// the front moves faster than the rear for the same size of stroke.
+///
+/// # Arguments
+///
+/// `rear`: (amplitude, peak velocity) pairs of the rear strokes in one direction
+/// `front`: (amplitude, peak velocity) pairs of the front strokes in the same direction
+pub fn balance_coefficient(rear: &[(f32, f32)], front: &[(f32, f32)]) -> Option<f32> {
+    let (rear_trend, front_trend) = (velocity_trend(rear)?, velocity_trend(front)?);
+    if rear_trend == 0.0 {
+        return None;
+    }
+
+    Some(front_trend / rear_trend)
+}
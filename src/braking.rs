@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data::sample_range;
+
+/// A spell of braking and what the suspension did during it
+#[derive(Clone, Copy, Debug)]
+pub struct BrakingEvent {
+    /// Time the braking started at in seconds
+    pub start: f32,
+    /// Time the braking ended at in seconds
+    pub end: f32,
+    pub front_brake: bool,
+    pub rear_brake: bool,
+    /// How far the fork compressed from where it was when the braking started, in % travel
+    pub fork_dive: f32,
+    /// How far the rear extended from where it was when the braking started, in % travel
+    pub rear_extension: f32,
+    /// Mean front travel minus mean rear travel during the braking in % travel, positive when the bike pitches forward
+    pub balance: f32,
+}
+
+/// Finds braking events in the brake channels
+///
+/// The brake sensors read differently at rest from bike to bike, so a brake counts as on when its reading is more
+/// than `threshold` from the resting reading, which is taken as the median of the channel.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct BrakeDetector {
+    /// Raw reading above the resting reading at which a brake counts as on
+    pub threshold: f32,
+    /// How far the reading has to drop back below the threshold for the brake to count as off again
+    pub hysteresis: f32,
+    /// Shortest braking in seconds that counts as an event
+    pub min_duration: f32,
+}
+
+impl Default for BrakeDetector {
+    fn default() -> BrakeDetector {
+        BrakeDetector {
+            threshold: 50.0,
+            hysteresis: 10.0,
+            min_duration: 0.2,
+        }
+    }
+}
+
+impl BrakeDetector {
+    /// (start, end) in seconds of each spell a brake channel is on for
+    pub fn brake_spans(&self, data: &[f32], polling_rate: f32) -> Vec<(f32, f32)> {
+        if data.is_empty() {
+            return Vec::new();
+        }
+
+        let mut sorted = data.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let resting = sorted[sorted.len() / 2];
+
+        let mut spans = Vec::new();
+        let mut start = None;
+        for (i, val) in data.iter().enumerate() {
+            let applied = val - resting;
+            match start {
+                None if applied > self.threshold => start = Some(i),
+                Some(s) if applied < self.threshold - self.hysteresis => {
+                    spans.push((s as f32 / polling_rate, i as f32 / polling_rate));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            spans.push((s as f32 / polling_rate, data.len() as f32 / polling_rate));
+        }
+
+        spans
+    }
+
+    /// Find the braking events of a run, braking on both brakes at once is one event
+    ///
+    /// # Arguments
+    ///
+    /// `front_brake`: (front brake readings, polling rate), None if the run has no front brake channel
+    /// `rear_brake`: (rear brake readings, polling rate), None if the run has no rear brake channel
+    /// `front_travel`: (front travel in %, polling rate)
+    /// `rear_travel`: (rear travel in %, polling rate)
+    ///
+    /// # Returns
+    ///
+    /// The events in time order
+    pub fn detect(&self, front_brake: Option<(&[f32], f32)>, rear_brake: Option<(&[f32], f32)>, front_travel: (&[f32], f32), rear_travel: (&[f32], f32)) -> Vec<BrakingEvent> {
+        // (start, end, front, rear) of every span from either brake, merged where they overlap
+        let mut spans: Vec<(f32, f32, bool, bool)> = Vec::new();
+        for (brake, is_front) in [(front_brake, true), (rear_brake, false)] {
+            if let Some((data, rate)) = brake {
+                spans.extend(self.brake_spans(data, rate).into_iter().map(|(s, e)| (s, e, is_front, !is_front)));
+            }
+        }
+        spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let merged = spans.into_iter().fold(Vec::new(), |mut merged: Vec<(f32, f32, bool, bool)>, span| {
+            match merged.last_mut() {
+                Some(last) if span.0 <= last.1 => {
+                    last.1 = f32::max(last.1, span.1);
+                    last.2 |= span.2;
+                    last.3 |= span.3;
+                }
+                _ => merged.push(span),
+            }
+            merged
+        });
+
+        merged.into_iter()
+            .filter(|(start, end, ..)| end - start >= self.min_duration)
+            .filter_map(|(start, end, front_brake, rear_brake)| {
+                let front = &front_travel.0[sample_range((start, end), front_travel.1, front_travel.0.len())];
+                let rear = &rear_travel.0[sample_range((start, end), rear_travel.1, rear_travel.0.len())];
+                let (front_start, rear_start) = (*front.first()?, *rear.first()?);
+
+                Some(BrakingEvent {
+                    start,
+                    end,
+                    front_brake,
+                    rear_brake,
+                    fork_dive: front.iter().fold(front_start, |max, t| max.max(*t)) - front_start,
+                    rear_extension: rear_start - rear.iter().fold(rear_start, |min, t| min.min(*t)),
+                    balance: mean(front) - mean(rear),
+                })
+            })
+            .collect()
+    }
+}
+
+fn mean(data: &[f32]) -> f32 {
+    data.iter().sum::<f32>() / data.len() as f32
+}
@@ -0,0 +1,86 @@
+use egui::Context;
+
+use crate::braking::BrakeDetector;
+use crate::data::Data;
+use crate::graph::suspension_graph::SuspensionGraph;
+
+/// Time either side of a braking event shown when zooming to it
+const ZOOM_PADDING: f64 = 0.5;
+
+/// Window listing the braking events of the run, clicking a start time zooms the suspension graph to the event
+pub struct BrakingTable {
+    pub open: bool,
+}
+
+impl BrakingTable {
+    pub fn new() -> BrakingTable {
+        BrakingTable { open: false }
+    }
+
+    /// Draw the window
+    ///
+    /// # Arguments
+    ///
+    /// `ctx`: The eGui context
+    /// `data`: The run data holding the "braking_events" field
+    /// `detector`: The brake detector settings from the config
+    ///
+    /// # Returns
+    ///
+    /// true if the detector settings were applied and the data needs to be recalculated
+    pub fn update(&mut self, ctx: &Context, data: &Data, detector: &mut BrakeDetector) -> bool {
+        let mut open = self.open;
+        let mut changed = false;
+
+        egui::Window::new("Braking").open(&mut open).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut detector.threshold).clamp_range(0.0..=4096.0).prefix("threshold: "));
+                ui.add(egui::DragValue::new(&mut detector.hysteresis).clamp_range(0.0..=4096.0).prefix("hysteresis: "));
+                ui.add(egui::DragValue::new(&mut detector.min_duration).clamp_range(0.0..=10.0).speed(0.01).prefix("min duration: ").suffix(" s"));
+                if ui.button("Detect braking").clicked() {
+                    changed = true;
+                }
+            });
+
+            ui.separator();
+
+            let events = match data.get_braking_events("braking_events".to_string()) {
+                Some(e) => e,
+                None => {
+                    ui.label("Braking needs the RB or FB channels and mapped travel");
+                    return;
+                }
+            };
+            ui.label(format!("{} braking events", events.len()));
+
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                egui::Grid::new("braking_grid").striped(true).show(ui, |ui| {
+                    for heading in ["start (s)", "duration (s)", "brakes", "fork dive (%)", "rear extension (%)", "balance (%)"] {
+                        ui.label(heading);
+                    }
+                    ui.end_row();
+
+                    for event in events {
+                        if ui.link(format!("{:.3}", event.start)).clicked() {
+                            SuspensionGraph::request_zoom(ctx, event.start as f64 - ZOOM_PADDING, event.end as f64 + ZOOM_PADDING);
+                        }
+                        let brakes = match (event.front_brake, event.rear_brake) {
+                            (true, true) => "both",
+                            (true, false) => "front",
+                            _ => "rear",
+                        };
+                        ui.label(format!("{:.2}", event.end - event.start));
+                        ui.label(brakes);
+                        ui.label(format!("{:.1}", event.fork_dive));
+                        ui.label(format!("{:.1}", event.rear_extension));
+                        ui.label(format!("{:+.1}", event.balance));
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+
+        self.open = open;
+        changed
+    }
+}
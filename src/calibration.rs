@@ -0,0 +1,366 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config_info::MAPPED_MAX;
+use crate::filter::solve_linear;
+
+/// How a calibration table turns a raw reading into mm
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum CalibrationFit {
+    /// Straight lines between the measured points, extended past the ends
+    PiecewiseLinear,
+    /// Least squares polynomial through the measured points
+    Polynomial { order: usize },
+}
+
+/// How well a calibration matches the measured points
+#[derive(Clone, Copy, Debug)]
+pub struct FitQuality {
+    pub r_squared: f32,
+    /// Root mean square error in mm
+    pub rms_error: f32,
+    /// Largest error in mm
+    pub max_error: f32,
+}
+
+/// Multi-point calibration of a pot, built from (raw reading, mm) pairs measured on the bike
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CalibrationTable {
+    /// (raw ADC reading, stroke in mm) pairs, kept sorted by raw reading
+    pub points: Vec<(f32, f32)>,
+    pub fit: CalibrationFit,
+    /// Polynomial coefficients, lowest order first, only used by a polynomial fit
+    #[serde(default)]
+    coefficients: Vec<f64>,
+    /// The raw readings are moved to -1..1 before the polynomial is applied to keep the fit well conditioned,
+    /// this holds the (centre, half width) of the readings
+    #[serde(default)]
+    raw_normalisation: (f64, f64),
+}
+
+impl Default for CalibrationTable {
+    fn default() -> CalibrationTable {
+        CalibrationTable {
+            points: Vec::new(),
+            fit: CalibrationFit::PiecewiseLinear,
+            coefficients: Vec::new(),
+            raw_normalisation: (0.0, 1.0),
+        }
+    }
+}
+
+impl CalibrationTable {
+    /// A table needs two points to give a stroke, and one more point than the order of a polynomial
+    pub fn is_valid(&self) -> bool {
+        match self.fit {
+            CalibrationFit::PiecewiseLinear => self.points.len() >= 2,
+            CalibrationFit::Polynomial { order } => self.points.len() > order && self.coefficients.len() == order + 1,
+        }
+    }
+
+    /// Sort the points and recalculate the fit, must be called after the points or the fit are changed
+    pub fn refit(&mut self) {
+        self.points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let (min, max) = match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => (first.0 as f64, last.0 as f64),
+            _ => (0.0, 0.0),
+        };
+        self.raw_normalisation = ((min + max) / 2.0, f64::max((max - min) / 2.0, 1.0));
+
+        self.coefficients = match self.fit {
+            CalibrationFit::PiecewiseLinear => Vec::new(),
+            CalibrationFit::Polynomial { order } => {
+                let normalised: Vec<(f64, f64)> = self.points.iter().map(|p| (self.normalise(p.0), p.1 as f64)).collect();
+                fit_polynomial(&normalised, order)
+            }
+        };
+    }
+
+    /// Stroke in mm for a raw reading
+    pub fn stroke_mm(&self, raw: f32) -> f32 {
+        if !self.is_valid() {
+            return 0.0;
+        }
+
+        match self.fit {
+            CalibrationFit::PiecewiseLinear => interpolate(&self.points, raw),
+            CalibrationFit::Polynomial { .. } => eval_polynomial(&self.coefficients, self.normalise(raw)) as f32,
+        }
+    }
+
+    fn normalise(&self, raw: f32) -> f64 {
+        (raw as f64 - self.raw_normalisation.0) / self.raw_normalisation.1
+    }
+
+    /// The largest stroke in the measured points, used as full travel when no stroke length is set
+    pub fn max_stroke_mm(&self) -> f32 {
+        self.points.iter().map(|p| p.1).fold(0.0, f32::max)
+    }
+
+    /// Mapped travel in % for a raw reading
+    ///
+    /// # Arguments
+    ///
+    /// `raw`: The raw reading
+    /// `full_stroke_mm`: The stroke which is 100% travel, if it is not positive the largest measured stroke is used
+    pub fn remap(&self, raw: f32, full_stroke_mm: f32) -> f32 {
+        let full_stroke_mm = if full_stroke_mm > 0.0 { full_stroke_mm } else { self.max_stroke_mm() };
+        if full_stroke_mm <= 0.0 {
+            return 0.0;
+        }
+
+        (self.stroke_mm(raw) / full_stroke_mm) * MAPPED_MAX
+    }
+
+    /// How well the calibration matches the measured points
+    ///
+    /// A piecewise linear fit goes through every point, so it is judged by leaving each inner point out in turn and
+    /// checking how well its neighbours predict it.
+    ///
+    /// # Returns
+    ///
+    /// None if there are not enough points to judge the fit
+    pub fn fit_quality(&self) -> Option<FitQuality> {
+        if !self.is_valid() {
+            return None;
+        }
+
+        let residuals: Vec<(f32, f32)> = match self.fit {
+            CalibrationFit::PiecewiseLinear => {
+                if self.points.len() < 3 {
+                    return None;
+                }
+                (1..self.points.len() - 1)
+                    .map(|i| {
+                        let neighbours = [self.points[i - 1], self.points[i + 1]];
+                        (self.points[i].1, interpolate(&neighbours, self.points[i].0))
+                    })
+                    .collect()
+            }
+            CalibrationFit::Polynomial { .. } => self.points.iter().map(|p| (p.1, self.stroke_mm(p.0))).collect(),
+        };
+
+        let count = residuals.len() as f32;
+        let mean = residuals.iter().map(|r| r.0).sum::<f32>() / count;
+        let total_sq: f32 = residuals.iter().map(|r| (r.0 - mean).powi(2)).sum();
+        let residual_sq: f32 = residuals.iter().map(|r| (r.0 - r.1).powi(2)).sum();
+
+        Some(FitQuality {
+            r_squared: if total_sq > 0.0 { 1.0 - residual_sq / total_sq } else { 1.0 },
+            rms_error: (residual_sq / count).sqrt(),
+            max_error: residuals.iter().map(|r| (r.0 - r.1).abs()).fold(0.0, f32::max),
+        })
+    }
+}
+
+/// Index of the first point of the segment `x` falls in, for points sorted by x, the end segments are extended
+///
+/// # Returns
+///
+/// None if there are fewer than two points to make a segment
+pub fn segment(points: &[(f32, f32)], x: f32) -> Option<usize> {
+    if points.len() < 2 {
+        return None;
+    }
+
+    let after = points.partition_point(|p| p.0 <= x);
+    Some(usize::clamp(after, 1, points.len() - 1) - 1)
+}
+
+/// Linear interpolation between points sorted by x, extended from the end segments
+///
+/// # Returns
+///
+/// The interpolated y, the y of the point if there is only one, 0.0 if there are none
+pub fn interpolate(points: &[(f32, f32)], x: f32) -> f32 {
+    let i = match segment(points, x) {
+        Some(i) => i,
+        None => return points.first().map_or(0.0, |p| p.1),
+    };
+    let ((x0, y0), (x1, y1)) = (points[i], points[i + 1]);
+
+    if x1 - x0 == 0.0 {
+        return y0;
+    }
+    y0 + (x - x0) * (y1 - y0) / (x1 - x0)
+}
+
+/// Least squares polynomial fit, coefficients lowest order first
+fn fit_polynomial(points: &[(f64, f64)], order: usize) -> Vec<f64> {
+    if points.len() <= order {
+        return Vec::new();
+    }
+
+    let terms = order + 1;
+    let mut normal = vec![vec![0.0_f64; terms]; terms];
+    let mut rhs = vec![0.0_f64; terms];
+    for (x, y) in points {
+        for r in 0..terms {
+            for (c, val) in normal[r].iter_mut().enumerate() {
+                *val += x.powi((r + c) as i32);
+            }
+            rhs[r] += y * x.powi(r as i32);
+        }
+    }
+
+    solve_linear(normal, rhs)
+}
+
+fn eval_polynomial(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().rev().fold(0.0, |acc, c| acc * x + c)
+}
+
+/// Time in seconds at the start and end of a static capture which is averaged to get the unweighted and sag readings
+pub const STATIC_SETTLE_TIME: f32 = 0.5;
+
+/// Readings found in a static capture, where the bike is held unweighted, then fully compressed, then sat on
+#[derive(Clone, Copy, Debug)]
+pub struct StaticCalibration {
+    /// Raw reading with the bike unweighted
+    pub zero: f32,
+    /// Raw reading at full compression
+    pub full: f32,
+    /// Raw reading with the rider sat on the bike
+    pub sag: f32,
+}
+
+impl StaticCalibration {
+    /// Static sag as a % of the travel between zero and full
+    pub fn sag_percent(&self) -> f32 {
+        if self.full == self.zero {
+            return 0.0;
+        }
+        (self.sag - self.zero) / (self.full - self.zero) * MAPPED_MAX
+    }
+}
+
+/// Find the zero, full travel and sag readings of a static capture
+///
+/// The zero and sag readings are the medians of the first and last `STATIC_SETTLE_TIME` seconds. Full travel is
+/// the reading furthest from zero, ignoring the most extreme 0.5% of samples so a single spike is not taken as
+/// full travel. The pot can be mounted either way round, so full may be below zero.
+///
+/// # Arguments
+///
+/// `data`: The raw readings of the channel
+/// `polling_rate`: The polling rate of the channel in Hz
+///
+/// # Returns
+///
+/// None if the capture is too short to hold the unweighted and sag periods
+pub fn analyse_static_capture(data: &[f32], polling_rate: f32) -> Option<StaticCalibration> {
+    let settle_samples = usize::max((STATIC_SETTLE_TIME * polling_rate) as usize, 1);
+    if data.len() < settle_samples * 3 {
+        return None;
+    }
+
+    let zero = median_of(&data[..settle_samples]);
+    let sag = median_of(&data[data.len() - settle_samples..]);
+
+    let mut deviations: Vec<f32> = data.iter().map(|d| d - zero).collect();
+    deviations.sort_by(|a, b| a.abs().total_cmp(&b.abs()));
+    let full = zero + deviations[((deviations.len() - 1) as f32 * 0.995) as usize];
+
+    Some(StaticCalibration { zero, full, sag })
+}
+
+fn median_of(data: &[f32]) -> f32 {
+    let mut sorted = data.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted[sorted.len() / 2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(points: Vec<(f32, f32)>, fit: CalibrationFit) -> CalibrationTable {
+        let mut table = CalibrationTable { points, fit, ..CalibrationTable::default() };
+        table.refit();
+        table
+    }
+
+    #[test]
+    fn polynomial_is_recovered() {
+        let (a, b, c) = (3.0, 0.05, 0.0002);
+        let points = (0..7).map(|i| {
+            let raw = 100.0 + 130.0 * i as f32;
+            (raw, a + b * raw + c * raw * raw)
+        }).collect();
+        let table = table(points, CalibrationFit::Polynomial { order: 2 });
+        assert!(table.is_valid());
+
+        // the raw readings 100..880 are moved to -1..1, so the coefficients are those of y(centre + half_width * u)
+        let (centre, half_width) = (490.0, 390.0);
+        let expected = [a + b * centre + c * centre * centre, b * half_width + 2.0 * c * centre * half_width, c * half_width * half_width];
+        assert_eq!(table.raw_normalisation, (centre as f64, half_width as f64));
+        for (coefficient, expected) in table.coefficients.iter().zip(expected) {
+            assert!((coefficient - expected as f64).abs() < 1e-3, "{} != {}", coefficient, expected);
+        }
+
+        let raw = 612.0;
+        assert!((table.stroke_mm(raw) - (a + b * raw + c * raw * raw)).abs() < 1e-2);
+        let quality = table.fit_quality().unwrap();
+        assert!(quality.max_error < 1e-2);
+        assert!((quality.r_squared - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn piecewise_linear_leave_one_out_is_exact_on_a_line() {
+        let points = (0..6).map(|i| (200.0 + 100.0 * i as f32, 12.5 * i as f32)).collect();
+        let table = table(points, CalibrationFit::PiecewiseLinear);
+
+        let quality = table.fit_quality().unwrap();
+        assert!((quality.r_squared - 1.0).abs() < 1e-6);
+        assert!(quality.rms_error < 1e-4);
+        assert!(quality.max_error < 1e-4);
+        // extended past the ends
+        assert!((table.stroke_mm(100.0) + 12.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn too_few_points_are_not_valid() {
+        let polynomial = table(vec![(100.0, 0.0), (500.0, 20.0), (900.0, 50.0)], CalibrationFit::Polynomial { order: 3 });
+        assert!(!polynomial.is_valid());
+        assert_eq!(polynomial.stroke_mm(500.0), 0.0);
+        assert!(polynomial.fit_quality().is_none());
+
+        let linear = table(vec![(100.0, 0.0)], CalibrationFit::PiecewiseLinear);
+        assert!(!linear.is_valid());
+        assert_eq!(linear.stroke_mm(500.0), 0.0);
+
+        // two points are valid but there is no inner point to leave out
+        let linear = table(vec![(100.0, 0.0), (900.0, 50.0)], CalibrationFit::PiecewiseLinear);
+        assert!(linear.is_valid());
+        assert!(linear.fit_quality().is_none());
+    }
+
+    #[test]
+    fn interpolate_handles_short_tables() {
+        assert_eq!(interpolate(&[], 3.0), 0.0);
+        assert_eq!(interpolate(&[(1.0, 7.0)], 3.0), 7.0);
+        assert_eq!(segment(&[(1.0, 7.0)], 3.0), None);
+        assert_eq!(interpolate(&[(1.0, 7.0), (1.0, 9.0)], 3.0), 7.0);
+        assert_eq!(interpolate(&[(0.0, 0.0), (2.0, 4.0), (4.0, 4.0)], 1.0), 2.0);
+    }
+
+    #[test]
+    fn static_capture_readings() {
+        let rate = 100.0;
+        let mut capture = vec![200.0; 50];
+        capture.extend((0..100).map(|i| 200.0 + 6.0 * i as f32));
+        capture.extend(vec![800.0; 100]);
+        capture.extend((0..50).map(|i| 800.0 - 9.0 * i as f32));
+        capture.extend(vec![350.0; 50]);
+        // a single spike past full travel is ignored
+        capture[200] = 1000.0;
+
+        let result = analyse_static_capture(&capture, rate).unwrap();
+        assert_eq!(result.zero, 200.0);
+        assert_eq!(result.full, 800.0);
+        assert_eq!(result.sag, 350.0);
+        assert!((result.sag_percent() - 25.0).abs() < 1e-4);
+
+        assert!(analyse_static_capture(&capture[..149], rate).is_none());
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use egui::{Color32, Context, Id};
+use egui_plot::{Line, Plot, PlotPoints, Points};
+
+use crate::calibration::{CalibrationFit, CalibrationTable};
+
+/// Number of points the fitted curve is drawn with
+const CURVE_POINTS: usize = 100;
+
+/// Window for entering the measured points of a multi-point calibration and checking the fit
+pub struct CalibrationWindow {
+    pub open: bool,
+}
+
+impl CalibrationWindow {
+    pub fn new() -> CalibrationWindow {
+        CalibrationWindow { open: false }
+    }
+
+    /// Draw the window
+    ///
+    /// # Arguments
+    ///
+    /// `ctx`: The eGui context
+    /// `remap_ref`: The remap reference being calibrated
+    /// `calibrations`: All of the calibrations in the config, keyed by remap reference
+    ///
+    /// # Returns
+    ///
+    /// true if the calibration was changed and the data needs to be recalculated
+    pub fn update(&mut self, ctx: &Context, remap_ref: &str, calibrations: &mut HashMap<String, CalibrationTable>) -> bool {
+        let mut open = self.open;
+        let mut changed = false;
+
+        egui::Window::new("Calibration table").open(&mut open).show(ctx, |ui| {
+            ui.label(format!("remap reference: {}", remap_ref));
+
+            let table = match calibrations.get_mut(remap_ref) {
+                Some(t) => t,
+                None => {
+                    if ui.button("Create calibration table").clicked() {
+                        calibrations.insert(remap_ref.to_string(), CalibrationTable::default());
+                    }
+                    return;
+                }
+            };
+
+            ui.label("raw reading / stroke (mm)");
+            let mut remove = None;
+            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                for (i, (raw, stroke)) in table.points.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(raw).clamp_range(0.0..=4096.0));
+                        ui.add(egui::DragValue::new(stroke).clamp_range(0.0..=500.0).speed(0.1));
+                        if ui.button("remove").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+            });
+            if let Some(i) = remove {
+                table.points.remove(i);
+            }
+            if ui.button("Add point").clicked() {
+                let last = table.points.last().copied().unwrap_or((0.0, 0.0));
+                table.points.push((last.0 + 50.0, last.1 + 5.0));
+            }
+
+            ui.horizontal(|ui| {
+                let mut polynomial = matches!(table.fit, CalibrationFit::Polynomial { .. });
+                ui.radio_value(&mut polynomial, false, "piecewise linear");
+                ui.radio_value(&mut polynomial, true, "polynomial");
+
+                table.fit = match (polynomial, table.fit) {
+                    (true, CalibrationFit::Polynomial { mut order }) => {
+                        ui.add(egui::DragValue::new(&mut order).clamp_range(1..=5).prefix("order: "));
+                        CalibrationFit::Polynomial { order }
+                    }
+                    (true, CalibrationFit::PiecewiseLinear) => CalibrationFit::Polynomial { order: 2 },
+                    (false, _) => CalibrationFit::PiecewiseLinear,
+                };
+            });
+
+            let (mut apply, mut delete) = (false, false);
+            ui.horizontal(|ui| {
+                apply = ui.button("Apply").clicked();
+                delete = ui.button("Delete table").clicked();
+            });
+            if apply {
+                table.refit();
+                changed = true;
+            }
+            if delete {
+                calibrations.remove(remap_ref);
+                changed = true;
+            }
+
+            let table = match calibrations.get(remap_ref) {
+                Some(t) if t.is_valid() => t,
+                _ => {
+                    ui.label("Not enough points for this fit, press apply once the points are entered");
+                    return;
+                }
+            };
+
+            match table.fit_quality() {
+                Some(quality) => {
+                    ui.label(format!(
+                        "R²: {:.4}    rms error: {:.2} mm    max error: {:.2} mm",
+                        quality.r_squared, quality.rms_error, quality.max_error
+                    ));
+                }
+                None => {
+                    ui.label("Add a third point to check the fit");
+                }
+            }
+
+            let measured: Vec<[f64; 2]> = table.points.iter().map(|(r, s)| [*r as f64, *s as f64]).collect();
+            let (raw_min, raw_max) = (table.points[0].0, table.points[table.points.len() - 1].0);
+            let fitted: Vec<[f64; 2]> = (0..=CURVE_POINTS)
+                .map(|i| {
+                    let raw = raw_min + (raw_max - raw_min) * i as f32 / CURVE_POINTS as f32;
+                    [raw as f64, table.stroke_mm(raw) as f64]
+                })
+                .collect();
+
+            Plot::new("calibration_fit")
+                .id(Id::new("calibration_fit"))
+                .width(400.0)
+                .height(250.0)
+                .x_axis_label("raw reading")
+                .y_axis_label("stroke (mm)")
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new(PlotPoints::new(fitted)).color(Color32::LIGHT_BLUE).name("fit"));
+                    plot_ui.points(Points::new(PlotPoints::new(measured)).radius(4.0).color(Color32::RED).name("measured"));
+                });
+        });
+
+        self.open = open;
+        changed
+    }
+}
@@ -0,0 +1,153 @@
+use std::env;
+
+use egui::{Color32, Context, Id};
+use egui_plot::{HLine, Line, LineStyle, Plot, PlotPoints};
+use rfd::FileDialog;
+
+use crate::calibration::{analyse_static_capture, StaticCalibration, STATIC_SETTLE_TIME};
+use crate::config_info::ConfigInfo;
+use crate::loader::Loader;
+
+/// Window which works out the zero, full travel and sag readings of a channel from a static capture
+///
+/// The capture is a short recording with the bike held unweighted, then fully compressed, then with the rider
+/// sat on it. The result is written to the config as the remap info of the chosen remap reference.
+pub struct CalibrationWizard {
+    pub open: bool,
+    path: String,
+    loader: Loader,
+    channel: String,
+    remap_ref: String,
+    result: Option<StaticCalibration>,
+}
+
+impl CalibrationWizard {
+    pub fn new() -> CalibrationWizard {
+        CalibrationWizard {
+            open: false,
+            path: String::new(),
+            loader: Loader::new(),
+            channel: String::new(),
+            remap_ref: String::new(),
+            result: None,
+        }
+    }
+
+    /// Draw the window
+    ///
+    /// # Arguments
+    ///
+    /// `ctx`: The eGui context
+    /// `config`: The config the new remap info is written to
+    ///
+    /// # Returns
+    ///
+    /// true if a remap info was written and the data needs to be recalculated
+    pub fn update(&mut self, ctx: &Context, config: &mut ConfigInfo) -> bool {
+        let mut open = self.open;
+        let mut written = false;
+
+        egui::Window::new("Calibration wizard").open(&mut open).show(ctx, |ui| {
+            ui.label(format!(
+                "Record {} s unweighted, push the suspension to full travel, then sit on the bike for the last {} s",
+                STATIC_SETTLE_TIME, STATIC_SETTLE_TIME
+            ));
+
+            ui.label(self.path.clone());
+            ui.horizontal(|ui| {
+                if ui.button("Select capture").clicked() {
+                    let mut res_dir = env::current_dir().unwrap_or_default();
+                    res_dir.push("resources");
+
+                    let file = FileDialog::new()
+                        .add_filter("Run Data", &["txt"])
+                        .set_directory(res_dir)
+                        .pick_file();
+
+                    if let Some(file_path) = file {
+                        self.path = file_path.to_str().unwrap().to_string();
+                    }
+                }
+                if ui.button("Load").clicked() && !self.path.is_empty() {
+                    self.loader.load(self.path.clone());
+                    self.channel.clear();
+                    self.result = None;
+                }
+            });
+
+            if self.loader.raw_pot_datas.is_empty() {
+                return;
+            }
+
+            let mut channel = self.channel.clone();
+            egui::ComboBox::new("wizard_channel_selector", "Channel")
+                .selected_text(channel.clone())
+                .show_ui(ui, |ui| {
+                    for tag in self.loader.raw_pot_datas.keys() {
+                        ui.selectable_value(&mut channel, tag.to_string(), tag);
+                    }
+                });
+            if channel != self.channel {
+                self.select_channel(channel);
+            }
+
+            let pot_data = match self.loader.raw_pot_datas.get(&self.channel) {
+                Some(p) => p,
+                None => return,
+            };
+            let result = match self.result {
+                Some(r) => r,
+                None => {
+                    ui.label("The capture is too short");
+                    return;
+                }
+            };
+
+            ui.label(format!("zero: {:.0}    full: {:.0}    sag reading: {:.0}", result.zero, result.full, result.sag));
+            ui.label(format!("static sag: {:.1} %", result.sag_percent()));
+
+            let capture: Vec<[f64; 2]> = pot_data.data.iter().enumerate()
+                .map(|(i, d)| [i as f64 / pot_data.polling_rate as f64, *d as f64])
+                .collect();
+            Plot::new("static_capture")
+                .id(Id::new("static_capture"))
+                .width(500.0)
+                .height(200.0)
+                .x_axis_label("time (s)")
+                .y_axis_label("raw reading")
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new(PlotPoints::new(capture)).color(Color32::LIGHT_BLUE));
+                    plot_ui.hline(HLine::new(result.zero).name("zero").style(LineStyle::dashed_loose()));
+                    plot_ui.hline(HLine::new(result.full).name("full").style(LineStyle::dashed_loose()));
+                    plot_ui.hline(HLine::new(result.sag).name("sag").style(LineStyle::dashed_loose()));
+                });
+
+            ui.horizontal(|ui| {
+                ui.label("remap reference: ");
+                ui.text_edit_singleline(&mut self.remap_ref);
+            });
+            if ui.button("Write remap info").clicked() && !self.remap_ref.is_empty() {
+                // keep the stroke lengths etc. of an existing entry, only the readings come from the capture
+                let mut remap_info = config.get_sus_remap_info(self.remap_ref.clone()).unwrap_or_default();
+                remap_info.set_zero_and_full(result.zero, result.full);
+                remap_info.static_sag = Some(result.sag_percent());
+                config.add_sus_remap_info(self.remap_ref.clone(), remap_info);
+                written = true;
+            }
+        });
+
+        self.open = open;
+        written
+    }
+
+    fn select_channel(&mut self, channel: String) {
+        self.result = None;
+        if let Some(pot_data) = self.loader.raw_pot_datas.get(&channel) {
+            let data: Vec<f32> = pot_data.data.iter().map(|d| *d as f32).collect();
+            self.result = analyse_static_capture(&data, pot_data.polling_rate as f32);
+            self.remap_ref = pot_data.remap_ref.clone();
+        }
+        self.channel = channel;
+    }
+}
+
@@ -1,6 +1,18 @@
 use std::{collections::HashMap, fs::{File, OpenOptions}, io::{BufReader, BufWriter}};
 use serde::{Deserialize, Serialize};
 
+use crate::activity::ActivityDetector;
+use crate::braking::BrakeDetector;
+use crate::calibration::CalibrationTable;
+use crate::filter::Filter;
+use crate::jumps::JumpDetector;
+use crate::leverage::LeverageCurve;
+use crate::orientation::OrientationFilter;
+use crate::packdown::PackDownDetector;
+use crate::spring::SpringModel;
+use crate::stroke::StrokeDetector;
+use crate::wheel_speed::WheelSensor;
+
 pub const DEFAULT_SUS_MIN: f32 = 0.0;
 pub const DEFAULT_SUS_MAX: f32 = 1024.0;
 pub const DEFAULT_SUS_DIFF: f32 = DEFAULT_SUS_MAX - DEFAULT_SUS_MIN;
@@ -11,11 +23,86 @@ pub struct SuspensionRemapInfo {
     pub stroke_len: f32,
     pub scale: f32,
     pub offset: f32,
+    /// Shock or fork stroke in mm covered by 0-100% of the mapped travel, 0 if unknown
+    #[serde(default)]
+    pub stroke_mm: f32,
+    /// Wheel travel in mm at full stroke, the same as `stroke_mm` for a fork, 0 if unknown
+    #[serde(default)]
+    pub wheel_travel_mm: f32,
+    /// The offset was measured by the calibration wizard, so the offset in a run file header does not replace it
+    #[serde(default)]
+    pub calibrated_offset: bool,
+    /// Static sag in % measured by the calibration wizard, used when a run has no time sat still before the riding
+    #[serde(default)]
+    pub static_sag: Option<f32>,
+}
+
+/// The unit travel is shown and analysed in
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum TravelUnit {
+    /// % of the mapped travel
+    Percent,
+    /// mm of wheel travel
+    Millimetres,
+}
+
+/// Travel thresholds, in % of travel, used to find bottom outs and top outs
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct TravelThresholds {
+    pub bottom_out: f32,
+    pub top_out: f32,
+    /// How far the travel has to come back past a threshold before the event is over
+    pub hysteresis: f32,
+}
+
+/// Settings which belong to a bike rather than a sensor
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct BikeConfig {
+    pub front_thresholds: TravelThresholds,
+    pub rear_thresholds: TravelThresholds,
+    /// Converts rear shock stroke to wheel travel, the rear is treated as linear while this is empty
+    #[serde(default)]
+    pub leverage_curve: LeverageCurve,
+    /// The rear shock spring, None when unknown
+    #[serde(default)]
+    pub rear_spring: Option<SpringModel>,
+    /// The fork spring, None when unknown
+    #[serde(default)]
+    pub front_spring: Option<SpringModel>,
+    /// The wheel speed sensor, used when a run has a wheel speed channel
+    #[serde(default)]
+    pub wheel_sensor: WheelSensor,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ConfigInfo {
     pub sus_remap_info: HashMap<String, SuspensionRemapInfo>,
+    /// Filters to apply to a channel, keyed by the channel tag in the run file header (e.g. "RS")
+    #[serde(default)]
+    pub channel_filters: HashMap<String, Filter>,
+    /// Settings used to find the strokes in the travel channels
+    #[serde(default)]
+    pub stroke_detector: StrokeDetector,
+    #[serde(default)]
+    pub bikes: HashMap<String, BikeConfig>,
+    /// Multi-point calibrations keyed by remap reference, used instead of the linear remap when present
+    #[serde(default)]
+    pub calibrations: HashMap<String, CalibrationTable>,
+    /// Settings used to find the riding portion of a run
+    #[serde(default)]
+    pub activity_detector: ActivityDetector,
+    /// Settings used to find jumps from the IMU and travel
+    #[serde(default)]
+    pub jump_detector: JumpDetector,
+    /// Settings used to find braking events in the brake channels
+    #[serde(default)]
+    pub brake_detector: BrakeDetector,
+    /// Settings used to find pack down in the strokes
+    #[serde(default)]
+    pub pack_down_detector: PackDownDetector,
+    /// Settings used to get the pitch and roll from the IMU
+    #[serde(default)]
+    pub orientation_filter: OrientationFilter,
 }
 
 impl Default for SuspensionRemapInfo {
@@ -24,16 +111,61 @@ impl Default for SuspensionRemapInfo {
             stroke_len: 100.0,
             scale: 1.0,
             offset: 1.0,
+            stroke_mm: 0.0,
+            wheel_travel_mm: 0.0,
+            calibrated_offset: false,
+            static_sag: None,
         }
     }
 }  
 
+impl Default for TravelThresholds {
+    fn default() -> TravelThresholds {
+        TravelThresholds {
+            bottom_out: 95.0,
+            top_out: 2.0,
+            hysteresis: 3.0,
+        }
+    }
+}
+
+impl TravelUnit {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TravelUnit::Percent => "%",
+            TravelUnit::Millimetres => "mm",
+        }
+    }
+}
+
+impl TravelThresholds {
+    /// The thresholds converted out of % of travel
+    ///
+    /// # Arguments
+    ///
+    /// `convert`: Converts a travel in % to the new unit, must be increasing
+    pub fn converted(&self, convert: impl Fn(f32) -> f32) -> TravelThresholds {
+        TravelThresholds {
+            bottom_out: convert(self.bottom_out),
+            top_out: convert(self.top_out),
+            hysteresis: convert(self.hysteresis) - convert(0.0),
+        }
+    }
+}
+
 impl SuspensionRemapInfo {
     pub fn calc_vals_from_min_and_max(&mut self, new_min: f32, new_max: f32) {
         let diff = new_max - new_min;
         self.scale = DEFAULT_SUS_DIFF / diff;
         self.offset = DEFAULT_SUS_MIN - new_min * self.scale;
     }
+    /// Set the remap so 0% is the `zero` reading and 100% is the `full` reading, `full` may be below `zero`
+    pub fn set_zero_and_full(&mut self, zero: f32, full: f32) {
+        self.offset = zero;
+        self.stroke_len = full - zero;
+        self.calibrated_offset = true;
+    }
+
     pub fn set_offset(&mut self, offset: f32) {
         self.offset = offset;
     }
@@ -46,6 +178,21 @@ impl SuspensionRemapInfo {
         (val / self.stroke_len) * 100.0
     }   
 
+    /// true if the stroke and wheel travel are known, so travel can be given in mm
+    pub fn has_mm(&self) -> bool {
+        self.stroke_mm > 0.0 && self.wheel_travel_mm > 0.0
+    }
+
+    /// Shock or fork stroke in mm for a mapped travel in %
+    pub fn remap_stroke_mm(&self, val: f32) -> f32 {
+        (val / MAPPED_MAX) * self.stroke_mm
+    }
+
+    /// Wheel travel in mm for a mapped travel in %, assuming a constant leverage ratio
+    pub fn remap_wheel_travel_mm(&self, val: f32) -> f32 {
+        (val / MAPPED_MAX) * self.wheel_travel_mm
+    }
+
     pub fn min(&self) -> f32 {
         self.remap(DEFAULT_SUS_MIN)
     }
@@ -67,6 +214,15 @@ impl ConfigInfo {
     pub fn load_blank() -> ConfigInfo {
         ConfigInfo {
             sus_remap_info: HashMap::new(),
+            channel_filters: HashMap::new(),
+            stroke_detector: StrokeDetector::default(),
+            bikes: HashMap::new(),
+            calibrations: HashMap::new(),
+            activity_detector: ActivityDetector::default(),
+            jump_detector: JumpDetector::default(),
+            brake_detector: BrakeDetector::default(),
+            pack_down_detector: PackDownDetector::default(),
+            orientation_filter: OrientationFilter::default(),
         }
     }
 
@@ -93,8 +249,12 @@ impl ConfigInfo {
         self.sus_remap_info.insert(key, info);
     }
 
+    /// Set the offset of a remap info to the one in a run file header, unless it was calibrated
     pub fn update_sus_remap_offset(&mut self,key:String, offset:f32){
         let mut a = self.get_sus_remap_info(key.clone()).unwrap();
+        if a.calibrated_offset {
+            return;
+        }
         a.set_offset(offset);
         self.set_sus_remap_info(key, a);
     }
@@ -107,4 +267,32 @@ impl ConfigInfo {
     pub fn get_sus_remap_info(&self, key: String) -> Option<SuspensionRemapInfo> {
         self.sus_remap_info.get(&key).copied()
     }
+
+    pub fn get_bike(&self, key: String) -> Option<BikeConfig> {
+        self.bikes.get(&key).cloned()
+    }
+
+    pub fn get_bike_mut(&mut self, key: String) -> Option<&mut BikeConfig> {
+        self.bikes.get_mut(&key)
+    }
+
+    pub fn add_bike(&mut self, key: String, bike: BikeConfig) {
+        self.bikes.insert(key, bike);
+    }
+
+    /// The calibration for a remap reference, if it has a usable one
+    pub fn get_calibration(&self, key: String) -> Option<&CalibrationTable> {
+        self.calibrations.get(&key).filter(|c| c.is_valid())
+    }
+
+    pub fn get_channel_filter(&self, channel: String) -> Option<Filter> {
+        self.channel_filters.get(&channel).copied()
+    }
+
+    pub fn set_channel_filter(&mut self, channel: String, filter: Option<Filter>) {
+        match filter {
+            Some(f) => self.channel_filters.insert(channel, f),
+            None => self.channel_filters.remove(&channel),
+        };
+    }
 }
\ No newline at end of file
@@ -0,0 +1,119 @@
+use crate::spring::SpringModel;
+use crate::stroke::{Stroke, StrokeDirection, VELOCITY_SPAN};
+
+/// Width of the shock velocity bins the strokes are grouped into, in mm/s
+pub const VELOCITY_BIN: f32 = 50.0;
+/// Fewest strokes a bin needs before its force is shown, single strokes are too noisy to trust
+pub const MIN_BIN_STROKES: usize = 2;
+
+/// Estimated damping force against shock velocity, like a dyno sheet
+///
+/// Rebound points have negative velocity and force so both directions can be drawn on the same axes.
+pub struct DamperCurve {
+    /// (shock velocity in mm/s, force in N) at the centre of each velocity bin
+    pub compression: Vec<(f32, f32)>,
+    pub rebound: Vec<(f32, f32)>,
+}
+
+/// Shock velocity and position at the fastest point of a stroke
+///
+/// # Returns
+///
+/// (speed in mm/s, shock stroke in mm) at the fastest sample
+fn fastest_point(shock_mm: &[f32], polling_rate: f32, stroke: &Stroke) -> (f32, f32) {
+    let velocity_span = usize::max((VELOCITY_SPAN * polling_rate / 2.0).round() as usize, 1);
+    let end_index = usize::min(stroke.end_index, shock_mm.len() - 1);
+
+    (stroke.start_index..=end_index)
+        .map(|i| {
+            let low = i.saturating_sub(velocity_span);
+            let high = usize::min(i + velocity_span, shock_mm.len() - 1);
+            ((shock_mm[high] - shock_mm[low]).abs() * polling_rate / (high - low) as f32, shock_mm[i])
+        })
+        .fold((0.0, 0.0), |fastest, point| if point.0 > fastest.0 { point } else { fastest })
+}
+
+/// Median force of each velocity bin with at least `MIN_BIN_STROKES` strokes
+fn binned_curve(points: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let bin_count = points.iter().map(|(v, _)| (*v / VELOCITY_BIN) as usize + 1).max().unwrap_or(0);
+    let mut bins = vec![Vec::new(); bin_count];
+    for (velocity, force) in points {
+        bins[(*velocity / VELOCITY_BIN) as usize].push(*force);
+    }
+
+    bins.iter_mut()
+        .enumerate()
+        .filter(|(_, forces)| forces.len() >= MIN_BIN_STROKES)
+        .map(|(i, forces)| {
+            forces.sort_by(|a, b| a.total_cmp(b));
+            ((i as f32 + 0.5) * VELOCITY_BIN, forces[forces.len() / 2])
+        })
+        .collect()
+}
+
+/// Rough damping curve of one end from the strokes seen on the trail
+///
+/// Without load cells the damping force is not measured. At the fastest point of a stroke the shock is not
+/// accelerating, so the damper holds the difference between the spring force and the load on the end. The rider's
+/// share of the weight is the load the spring holds at static sag, so the damping force is taken as the spring force
+/// beyond the force at sag. That holds well for rebound, where only the spring and the weight act, and is a rough
+/// guide for compression, where the unknown load from the ground also acts.
+///
+/// # Arguments
+///
+/// `shock_mm`: The shock stroke in mm
+/// `polling_rate`: Polling rate of the channel
+/// `strokes`: The strokes, with indices into `shock_mm`
+/// `spring`: The spring of the end
+/// `sag_mm`: The shock stroke in mm at static sag
+pub fn estimate(shock_mm: &[f32], polling_rate: f32, strokes: &[Stroke], spring: &SpringModel, sag_mm: f32) -> DamperCurve {
+    let (mut compression, mut rebound) = (Vec::new(), Vec::new());
+    let sag_force = spring.force(sag_mm);
+    if !shock_mm.is_empty() {
+        for stroke in strokes {
+            let (velocity, position) = fastest_point(shock_mm, polling_rate, stroke);
+            let force = spring.force(position) - sag_force;
+            match stroke.direction {
+                StrokeDirection::Compression => compression.push((velocity, force)),
+                StrokeDirection::Rebound => rebound.push((velocity, force)),
+            }
+        }
+    }
+
+    DamperCurve {
+        compression: binned_curve(&compression),
+        rebound: binned_curve(&rebound).into_iter().map(|(v, f)| (-v, -f)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stroke::StrokeDetector;
+
+    const RATE: f32 = 1000.0;
+    const SPRING: SpringModel = SpringModel::Coil { rate: 10.0, preload: 0.0 };
+
+    #[test]
+    fn stroke_through_sag_has_no_damping() {
+        // 40 ± 20 mm every 0.4 s, fastest at 40 mm at 20π / 0.2 ≈ 314 mm/s
+        let shock_mm: Vec<f32> = (0..4000)
+            .map(|i| 40.0 + 20.0 * (2.0 * std::f32::consts::PI * i as f32 / (0.4 * RATE)).cos())
+            .collect();
+        let strokes = StrokeDetector::default().detect(&shock_mm, RATE);
+
+        // with the sag at the middle of the stroke the spring is holding just the weight there
+        let curve = estimate(&shock_mm, RATE, &strokes, &SPRING, 40.0);
+        assert_eq!(curve.compression.len(), 1);
+        assert_eq!(curve.rebound.len(), 1);
+        assert_eq!(curve.compression[0].0, 325.0);
+        assert_eq!(curve.rebound[0].0, -325.0);
+        assert!(curve.compression[0].1.abs() < 5.0);
+        assert!(curve.rebound[0].1.abs() < 5.0);
+
+        // 20 mm deeper than sag the spring pushes 200 N past the weight, which the damper holds back
+        let curve = estimate(&shock_mm, RATE, &strokes, &SPRING, 20.0);
+        assert!((curve.compression[0].1 - 200.0).abs() < 5.0);
+        assert!((curve.rebound[0].1 + 200.0).abs() < 5.0);
+    }
+}
@@ -6,10 +6,7 @@ use std::{io, thread};
 use std::io::prelude::*;
 
 pub const BUFF_SIZE: usize = 4500;
-pub const FREQUENCY: f32 = 1000.0;
 
-///The minimum period of a compression + rebound in the data. Used for turning point detection
-pub const MIN_PERIOD: f64 = 0.02; 
 /// allows more polymorphic approach to storing different data typed to Data
 #[allow(dead_code)]
 pub enum TelemData {
@@ -24,6 +21,18 @@ pub enum TelemData {
     F64V(Vec<f64>),
     PlotPointV(Vec<PlotPoint>),
     LineManager(LineManager),
+    StrokeV(Vec<Stroke>),
+    TravelEventV(Vec<TravelEvent>),
+    Sag(SagMetrics),
+    ActivitySegmentV(Vec<ActivitySegment>),
+    Annotations(RunAnnotations),
+    JumpV(Vec<Jump>),
+    BrakingEventV(Vec<BrakingEvent>),
+    Spectrogram(Spectrogram),
+    TravelStats(TravelStats),
+    LineFit(LineFit),
+    PackDownV(Vec<PackDown>),
+    DistanceMap(DistanceMap),
 }
 /// Hash map containing multipil data entries
  pub struct Data {
@@ -138,6 +147,90 @@ impl Data {
     }
 
 
+    pub fn get_strokes(&self, field: String) -> Option<&Vec<Stroke>> {
+        if let Ok(TelemData::StrokeV(res)) = self.get(field) {
+            return Some(res);
+        }
+        None
+    }
+
+    pub fn get_travel_events(&self, field: String) -> Option<&Vec<TravelEvent>> {
+        if let Ok(TelemData::TravelEventV(res)) = self.get(field) {
+            return Some(res);
+        }
+        None
+    }
+
+    pub fn get_sag(&self, field: String) -> Option<&SagMetrics> {
+        if let Ok(TelemData::Sag(res)) = self.get(field) {
+            return Some(res);
+        }
+        None
+    }
+
+    pub fn get_activity_segments(&self, field: String) -> Option<&Vec<ActivitySegment>> {
+        if let Ok(TelemData::ActivitySegmentV(res)) = self.get(field) {
+            return Some(res);
+        }
+        None
+    }
+
+    pub fn get_annotations(&self, field: String) -> Option<&RunAnnotations> {
+        if let Ok(TelemData::Annotations(res)) = self.get(field) {
+            return Some(res);
+        }
+        None
+    }
+
+    pub fn get_jumps(&self, field: String) -> Option<&Vec<Jump>> {
+        if let Ok(TelemData::JumpV(res)) = self.get(field) {
+            return Some(res);
+        }
+        None
+    }
+
+    pub fn get_braking_events(&self, field: String) -> Option<&Vec<BrakingEvent>> {
+        if let Ok(TelemData::BrakingEventV(res)) = self.get(field) {
+            return Some(res);
+        }
+        None
+    }
+
+    pub fn get_spectrogram(&self, field: String) -> Option<&Spectrogram> {
+        if let Ok(TelemData::Spectrogram(res)) = self.get(field) {
+            return Some(res);
+        }
+        None
+    }
+
+    pub fn get_travel_stats(&self, field: String) -> Option<&TravelStats> {
+        if let Ok(TelemData::TravelStats(res)) = self.get(field) {
+            return Some(res);
+        }
+        None
+    }
+
+    pub fn get_line_fit(&self, field: String) -> Option<&LineFit> {
+        if let Ok(TelemData::LineFit(res)) = self.get(field) {
+            return Some(res);
+        }
+        None
+    }
+
+    pub fn get_pack_downs(&self, field: String) -> Option<&Vec<PackDown>> {
+        if let Ok(TelemData::PackDownV(res)) = self.get(field) {
+            return Some(res);
+        }
+        None
+    }
+
+    pub fn get_distance_map(&self, field: String) -> Option<&DistanceMap> {
+        if let Ok(TelemData::DistanceMap(res)) = self.get(field) {
+            return Some(res);
+        }
+        None
+    }
+
     pub fn get_f64pv(&self, field: String) -> &Vec<(f32, f32)> {
         if let Ok(TelemData::F32PV(res)) = self.get(field) {
             return &res;
@@ -147,96 +240,63 @@ impl Data {
     }
 
 
-    /// generates a list of the turning points for a graph
+    /// Detects the strokes in a travel channel and stores them, along with the turning points and the
+    /// (amplitude, peak velocity) pairs the displacement-velocity graphs use
     ///
     /// # Arguments
     ///
-    /// * `field` - the lable of where the data will be stored in Data
-    /// * `data` - the LineManager to take the line data from
+    /// * `end` - "rear" or "front", the strokes, rebound and compression pairs and turning points are stored as
+    ///   `end` + "_strokes", "_rebound", "_compression" and "_turning"
+    /// * `data` - the travel samples
+    /// * `range` - the samples to keep the strokes and turning points of, strokes are kept if they start in it
+    /// * `polling_rate` - the polling rate of the channel
+    /// * `detector` - the stroke detector settings
     ///
     /// # Return
     /// result of adding the data generated to self
-    pub fn set_turning_points(&mut self, rebounds_field: String, compressions_field: String, turning_point_field: String, data: &Vec<f32>, front:bool) -> Result<(), &str> {
-        let mut turning_points = Vec::new();
-
-        let line_choice = data;
-
-        let turning_range = ((FREQUENCY * MIN_PERIOD as f32)/2.0) as usize;
-        let mut outer_index = turning_range;
+    pub fn set_strokes(&mut self, end: String, data: &[f32], range: Range<usize>, polling_rate: f32, detector: &StrokeDetector) -> Result<(), &str> {
+        let turning_points: Vec<(f32, f32)> = detector.turning_points(data).iter().filter(|i| range.contains(i)).map(|i| {
+            (*i as f32 / polling_rate, data[*i])
+        }).collect();
+        let mut strokes = detector.detect(data, polling_rate);
+        strokes.retain(|s| range.contains(&s.start_index));
 
-        let mut decreasing = false;
-        turning_points.push((0.0,line_choice[0]));
-        if turning_points[0].1 > line_choice[5] {
-            decreasing = true;
+        let mut compressions = Vec::new();
+        let mut rebounds = Vec::new();
+        for stroke in &strokes {
+            match stroke.direction {
+                StrokeDirection::Compression => compressions.push((stroke.amplitude, stroke.peak_velocity)),
+                StrokeDirection::Rebound => rebounds.push((stroke.amplitude, stroke.peak_velocity)),
+            }
         }
 
-        let mut median = line_choice.clone(); 
-        for outer in (turning_range/2)..line_choice.len()-(turning_range/2) {
-            median[outer] = self.data_average_raw(&data[(outer - turning_range/2)..(outer + turning_range/2)].to_vec())
+        // the fits are left out when there are too few strokes, the graph says so instead
+        for (direction, pairs) in [("_compression_fit", &compressions), ("_rebound_fit", &rebounds)] {
+            if let Some(fit) = regression::theil_sen(pairs) {
+                self.set(end.clone() + direction, TelemData::LineFit(fit)).unwrap();
+            }
         }
 
-        let mut last_point = data[0];
-        for plot_point in &data[turning_range..line_choice.len() - turning_range] {
+        self.set(end.clone() + "_compression", TelemData::F32PV(compressions)).unwrap();
+        self.set(end.clone() + "_rebound", TelemData::F32PV(rebounds)).unwrap();
+        self.set(end.clone() + "_strokes", TelemData::StrokeV(strokes)).unwrap();
 
+        self.set(end + "_turning", TelemData::PlotPointV(to_plot_points(&turning_points)))
+    }
 
-            let (mut back_average, mut front_average) = (0.0, 0.0);
-            for inner_index in 0..turning_range-1 {
-                front_average += (plot_point - median[outer_index + inner_index])as f32;
-                back_average += (plot_point - median[outer_index - inner_index]) as f32;
-            }
-
-            // if decreasing dosent match the direction that the graph is heading in flip it
-            if (decreasing == (back_average > front_average +  (if decreasing == true{-10.0 }else{10.0 }))) && (plot_point - last_point).abs() >1.0{
-                decreasing ^= true;
-                turning_points.push((outer_index as f32 / FREQUENCY,plot_point.clone()));
-                last_point = turning_points.last().unwrap().1
-            };
-            outer_index += 1
-        }
- 
-        //line_choice[last..current].to_vec()
-        
-        let compressions_rebounds = Self::set_compressions_rebounds(turning_points.clone(),&data,front);
-        
-        println!("{}, {}", compressions_rebounds[0].len(), compressions_rebounds[1].len());
-        //self.set_displacements(displacements_field, &turning_points).unwrap();
-        self.set(compressions_field, TelemData::F32PV(compressions_rebounds[0].clone())).unwrap();
-        self.set(rebounds_field, TelemData::F32PV(compressions_rebounds[1].clone())).unwrap();
-        
-
-        self.set(turning_point_field, TelemData::PlotPointV(to_plot_points(&turning_points))) 
-
-    }
-    pub fn set_compressions_rebounds(turning_points: Vec<(f32,f32)>, line_choice:&Vec<f32>, front:bool)->[Vec<(f32,f32)>;2]{
-        let mut compressions = Vec::new();
-        let mut rebounds = Vec::new();
-        let mut last = turning_points[0];
-        if turning_points.len() > 1{
-
-            for point in 1..(turning_points.len()-2){   // +ve disp and it is a rebound
-                let current_disp = turning_points[point].1 - last.1;
-                let mut max_speed = 0.0_f32;
-                let mut last_point_y = line_choice[(last.0 * FREQUENCY) as usize];
-                for inner in line_choice[(last.0 * FREQUENCY) as usize..(turning_points[point].0 * FREQUENCY) as usize].to_vec(){
-                    if (inner - last_point_y).abs() > max_speed.abs(){
-                        max_speed = (inner - last_point_y).abs()
-                    }
-                    last_point_y = inner
-                } 
-                if current_disp.abs() > 5.0{
-                    max_speed = (max_speed.abs() /  if front{5.05}else{4.55})* FREQUENCY;
-                    if current_disp < 0.0{
-                        compressions.push((current_disp.abs(),max_speed.abs()));
-                    }else{
-                        rebounds.push((current_disp.abs(),max_speed.abs()));
-                    }
-                    //max_speed = (max_speed.abs() / if !front {4.55}else{13.65}) * FREQUENCY  ;
-                }
-                last = turning_points[point];
-            }
+    /// Sets the travel statistics of one end, nothing is stored if there is no data
+    ///
+    /// # Arguments
+    ///
+    /// `field`: The field to store the statistics under
+    /// `data`: The travel over the analysis range
+    /// `polling_rate`: Polling rate of the channel
+    /// `full_travel`: The travel at full compression in the same unit as the data
+    pub fn set_travel_stats(&mut self, field: String, data: &[f32], polling_rate: f32, full_travel: f32) -> Result<(), &str> {
+        match TravelStats::compute(data, polling_rate, full_travel) {
+            Some(stats) => self.set(field, TelemData::TravelStats(stats)),
+            None => Ok(()),
         }
-
-        [compressions,rebounds]
     }
 
     /// Sets the Displacement value for the given data
@@ -265,13 +325,20 @@ impl Data {
 
 
     /// sets sorts data in set Bins
+    ///
+    /// # Arguments
+    /// * 'bin_count' number of equal width bins between min_val and max_val
+    /// * 'min_val' 'max_val' the range binned, values outside of it are not counted
     /// # Returns
     /// sorted data
-    pub fn set_count(&mut self, field: String, data: &Vec<f32>, bin_count: usize, max_val: f64, reverse: bool) -> Result<(), &str> {
+    pub fn set_count(&mut self, field: String, data: &Vec<f32>, bin_count: usize, min_val: f64, max_val: f64, reverse: bool) -> Result<(), &str> {
         let mut data_count = vec![0u32; bin_count];
         for point in data.iter(){
-            let mut index = ((*point as f64/max_val) * (bin_count as f64-1.0)).round() as usize;
-            index = usize::clamp(index, 0, bin_count - 1);
+            let position = (*point as f64 - min_val) / (max_val - min_val);
+            if !(0.0..=1.0).contains(&position) {
+                continue;
+            }
+            let mut index = usize::min((position * bin_count as f64) as usize, bin_count - 1);
             if reverse {index = bin_count -1 - index ;}
             data_count[index] += 1;
         }
@@ -279,16 +346,24 @@ impl Data {
         self.set(field, TelemData::U32V(data_count))
     }
 
-    pub fn remapped_1d(&mut self, data: &Vec<f32>, remap_info: &SuspensionRemapInfo) -> Vec<f32> {
+    /// Remaps a single raw reading, through the calibration table if there is one
+    fn remap_val(val: f32, remap_info: &SuspensionRemapInfo, calibration: Option<&CalibrationTable>) -> f32 {
+        match calibration {
+            Some(table) => table.remap(val, remap_info.stroke_mm),
+            None => remap_info.remap(val),
+        }
+    }
+
+    pub fn remapped_1d(&mut self, data: &Vec<f32>, remap_info: &SuspensionRemapInfo, calibration: Option<&CalibrationTable>) -> Vec<f32> {
         data.iter().map(|d| {
-            let new_val = remap_info.remap(*d);
+            let new_val = Self::remap_val(*d, remap_info, calibration);
             new_val
         }).collect()
     }
     
-    pub fn remapped_1d_with_clamp(&mut self, data: &Vec<f32>, remap_info: &SuspensionRemapInfo, min: f32, max: f32) -> Vec<f32> {
+    pub fn remapped_1d_with_clamp(&mut self, data: &Vec<f32>, remap_info: &SuspensionRemapInfo, calibration: Option<&CalibrationTable>, min: f32, max: f32) -> Vec<f32> {
         data.iter().map(|d| {
-            let new_val = f32::clamp(remap_info.remap(*d), min, max);
+            let new_val = f32::clamp(Self::remap_val(*d, remap_info, calibration), min, max);
             new_val
         }).collect()
     }
@@ -309,6 +384,19 @@ impl Data {
 
 
 
+/// Sample indices of a channel covering a time range, clamped to the length of the channel
+///
+/// # Arguments
+///
+/// * `range` - (start, end) in seconds
+/// * `polling_rate` - the polling rate of the channel
+/// * `len` - the number of samples in the channel
+pub fn sample_range(range: (f32, f32), polling_rate: f32, len: usize) -> Range<usize> {
+    let to_index = |t: f32| usize::min((t.max(0.0) * polling_rate) as usize, len);
+    let start = to_index(range.0);
+    start..usize::max(to_index(range.1), start)
+}
+
 #[derive(Clone)]
 
 pub struct Buff {
@@ -391,11 +479,24 @@ impl Buff {
     }
 }
 
-use std::ops::{Add, Bound, Mul, RangeBounds};
+use std::ops::{Add, Bound, Mul, Range, RangeBounds};
 
 use egui_plot::{Line, PlotPoint};
 
+use crate::activity::ActivitySegment;
+use crate::braking::BrakingEvent;
+use crate::calibration::CalibrationTable;
+use crate::distance::DistanceMap;
 use crate::config_info::SuspensionRemapInfo;
+use crate::jumps::Jump;
+use crate::packdown::PackDown;
+use crate::regression::{self, LineFit};
+use crate::sag::SagMetrics;
+use crate::sections::RunAnnotations;
+use crate::spectrum::Spectrogram;
+use crate::stroke::{Stroke, StrokeDetector, StrokeDirection};
+use crate::travel_events::TravelEvent;
+use crate::travel_stats::TravelStats;
 use crate::graph::line_manager::LineManager;
 use crate::graph::ToPlotPoint;
 
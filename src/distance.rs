@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+use crate::activity::ActivitySegment;
+
+/// Time over which the IMU speed estimate decays towards zero, stops the integrated acceleration drifting away
+const IMU_SPEED_DECAY: f32 = 20.0;
+
+/// What the x axis of the time based graphs shows
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum XAxis {
+    /// Seconds from the start of the run
+    Time,
+    /// Metres from the start of the run
+    Distance,
+}
+
+impl XAxis {
+    pub fn name(&self) -> &'static str {
+        match self {
+            XAxis::Time => "time",
+            XAxis::Distance => "distance",
+        }
+    }
+
+    /// Label of the axis with its unit
+    pub fn label(&self) -> &'static str {
+        match self {
+            XAxis::Time => "time (s)",
+            XAxis::Distance => "distance (m)",
+        }
+    }
+}
+
+/// Distance travelled along a run at evenly spaced times, to convert between times and distances
+pub struct DistanceMap {
+    /// Metres travelled at each sample
+    pub distance: Vec<f32>,
+    pub polling_rate: f32,
+}
+
+impl DistanceMap {
+    /// Integrate a speed channel
+    ///
+    /// # Arguments
+    ///
+    /// `speed`: The speed in m/s
+    /// `polling_rate`: Polling rate of the speed channel
+    pub fn from_speed(speed: &[f32], polling_rate: f32) -> DistanceMap {
+        let mut distance = Vec::with_capacity(speed.len());
+        let mut travelled = 0.0;
+        for (i, s) in speed.iter().enumerate() {
+            if i > 0 {
+                travelled += (speed[i - 1] + s) / 2.0 / polling_rate;
+            }
+            distance.push(travelled);
+        }
+
+        DistanceMap { distance, polling_rate }
+    }
+
+    /// Metres travelled by `time` seconds, clamped to the start and end of the run
+    pub fn distance_at(&self, time: f32) -> f32 {
+        if self.distance.is_empty() {
+            return 0.0;
+        }
+        let position = (time * self.polling_rate).clamp(0.0, (self.distance.len() - 1) as f32);
+        let i = position as usize;
+        let next = usize::min(i + 1, self.distance.len() - 1);
+        self.distance[i] + (self.distance[next] - self.distance[i]) * (position - i as f32)
+    }
+
+    /// First time the run reached `distance` metres, clamped to the start and end of the run
+    pub fn time_at(&self, distance: f32) -> f32 {
+        let i = self.distance.partition_point(|d| *d < distance);
+        if i == 0 || i == self.distance.len() {
+            return usize::min(i, self.distance.len().saturating_sub(1)) as f32 / self.polling_rate;
+        }
+
+        // interpolate inside the sample, the distance is only flat while stopped where i is already the first sample
+        let (before, after) = (self.distance[i - 1], self.distance[i]);
+        let fraction = if after > before { (distance - before) / (after - before) } else { 0.0 };
+        (i as f32 - 1.0 + fraction) / self.polling_rate
+    }
+
+    /// Total distance of the run in metres
+    pub fn total(&self) -> f32 {
+        self.distance.last().copied().unwrap_or(0.0)
+    }
+}
+
+/// Rough speed from the forward acceleration of the bike
+///
+/// Integrated acceleration drifts, so the speed is held at zero through idle segments, never goes negative and slowly
+/// decays towards zero. It is good enough to line runs up by distance but not to read speeds from.
+///
+/// # Arguments
+///
+/// `forward_accel`: Acceleration along the bike with gravity removed in m/s²
+/// `polling_rate`: Polling rate of the IMU
+/// `segments`: The riding and idle segments of the run
+///
+/// # Returns
+///
+/// The speed in m/s at each IMU sample
+pub fn imu_speed(forward_accel: &[f32], polling_rate: f32, segments: &[ActivitySegment]) -> Vec<f32> {
+    let dt = 1.0 / polling_rate;
+    let decay = 1.0 - dt / IMU_SPEED_DECAY;
+    let idle = |time: f32| segments.iter().any(|s| !s.riding && time >= s.start && time < s.end);
+
+    let mut speed = 0.0;
+    forward_accel.iter().enumerate()
+        .map(|(i, a)| {
+            speed = if idle(i as f32 * dt) { 0.0 } else { f32::max((speed + a * dt) * decay, 0.0) };
+            speed
+        })
+        .collect()
+}
@@ -0,0 +1,307 @@
+use std::f32::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+/// The filters which can be applied to a channel before it is analysed
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    /// Centred moving average over `window` samples
+    MovingAverage { window: usize },
+    /// Centred running median over `window` samples, good at removing single sample spikes
+    Median { window: usize },
+    /// Zero phase Butterworth low-pass, `order` is rounded up to an even number
+    Butterworth { cutoff: f32, order: usize },
+    /// Savitzky-Golay smoothing, fits a polynomial of `poly_order` over `window` samples
+    SavitzkyGolay { window: usize, poly_order: usize },
+}
+
+impl Filter {
+    /// The filters in the order they are shown in the UI, with sensible default parameters
+    pub fn defaults() -> [Filter; 4] {
+        [
+            Filter::MovingAverage { window: 10 },
+            Filter::Median { window: 5 },
+            Filter::Butterworth { cutoff: 20.0, order: 2 },
+            Filter::SavitzkyGolay { window: 21, poly_order: 3 },
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Filter::MovingAverage { .. } => "Moving average",
+            Filter::Median { .. } => "Median",
+            Filter::Butterworth { .. } => "Butterworth low-pass",
+            Filter::SavitzkyGolay { .. } => "Savitzky-Golay",
+        }
+    }
+
+    /// Apply the filter to a channel
+    ///
+    /// # Arguments
+    ///
+    /// `data`: The samples to filter
+    /// `sample_rate`: The polling rate of the channel in Hz
+    ///
+    /// # Returns
+    ///
+    /// A vector the same length as `data` holding the filtered samples
+    pub fn apply(&self, data: &[f32], sample_rate: f32) -> Vec<f32> {
+        if data.is_empty() {
+            return Vec::new();
+        }
+
+        match *self {
+            Filter::MovingAverage { window } => moving_average(data, window),
+            Filter::Median { window } => median(data, window),
+            Filter::Butterworth { cutoff, order } => butterworth(data, cutoff, order, sample_rate),
+            Filter::SavitzkyGolay { window, poly_order } => savitzky_golay(data, window, poly_order),
+        }
+    }
+}
+
+/// Centred moving average, the window shrinks at the ends of the data
+pub fn moving_average(data: &[f32], window: usize) -> Vec<f32> {
+    let half = window.max(1) / 2;
+    let mut prefix = Vec::with_capacity(data.len() + 1);
+    prefix.push(0.0_f64);
+    for d in data {
+        prefix.push(prefix.last().unwrap() + *d as f64);
+    }
+
+    (0..data.len())
+        .map(|i| {
+            let start = i.saturating_sub(half);
+            let end = usize::min(i + half + 1, data.len());
+            ((prefix[end] - prefix[start]) / (end - start) as f64) as f32
+        })
+        .collect()
+}
+
+/// Centred running median, the window shrinks at the ends of the data
+pub fn median(data: &[f32], window: usize) -> Vec<f32> {
+    let half = window.max(1) / 2;
+    let mut sorted = Vec::with_capacity(half * 2 + 1);
+
+    (0..data.len())
+        .map(|i| {
+            let start = i.saturating_sub(half);
+            let end = usize::min(i + half + 1, data.len());
+            sorted.clear();
+            sorted.extend_from_slice(&data[start..end]);
+            sorted.sort_by(|a, b| a.total_cmp(b));
+            sorted[sorted.len() / 2]
+        })
+        .collect()
+}
+
+/// Zero phase Butterworth low-pass made from cascaded biquads, run forwards then backwards
+pub fn butterworth(data: &[f32], cutoff: f32, order: usize, sample_rate: f32) -> Vec<f32> {
+    // a cutoff at or above nyquist would make the filter unstable
+    let cutoff = f32::clamp(cutoff, 0.01, sample_rate * 0.49);
+    let sections = usize::max((order + 1) / 2, 1);
+    let pole_count = (sections * 2) as f32;
+
+    let mut filtered = data.to_vec();
+    for k in 0..sections {
+        let q = 1.0 / (2.0 * ((2 * k + 1) as f32 * PI / (2.0 * pole_count)).cos());
+        let biquad = Biquad::low_pass(cutoff, q, sample_rate);
+
+        biquad.run(&mut filtered);
+        filtered.reverse();
+        biquad.run(&mut filtered);
+        filtered.reverse();
+    }
+
+    filtered
+}
+
+/// Savitzky-Golay smoothing, the ends of the data are fitted with the nearest full window
+pub fn savitzky_golay(data: &[f32], window: usize, poly_order: usize) -> Vec<f32> {
+    // the window has to be odd and longer than the polynomial
+    let mut window = usize::max(window, poly_order + 2) | 1;
+    if window > data.len() {
+        window = if data.len() % 2 == 0 { data.len() - 1 } else { data.len() };
+    }
+    let poly_order = usize::min(poly_order, window.saturating_sub(1));
+    let half = window / 2;
+
+    if window < 3 {
+        return data.to_vec();
+    }
+
+    let centre_weights = savitzky_golay_weights(half, poly_order, 0);
+    let mut filtered = data.to_vec();
+
+    for i in half..data.len() - half {
+        filtered[i] = convolve(&data[i - half..=i + half], &centre_weights);
+    }
+    for i in 0..half {
+        let weights = savitzky_golay_weights(half, poly_order, i as i32 - half as i32);
+        filtered[i] = convolve(&data[0..window], &weights);
+
+        let weights = savitzky_golay_weights(half, poly_order, half as i32 - i as i32);
+        let end = data.len() - 1 - i;
+        filtered[end] = convolve(&data[data.len() - window..], &weights);
+    }
+
+    filtered
+}
+
+fn convolve(data: &[f32], weights: &[f64]) -> f32 {
+    data.iter().zip(weights).map(|(d, w)| *d as f64 * w).sum::<f64>() as f32
+}
+
+/// Weights which evaluate a least squares polynomial fit of the window at offset `eval_at` from the centre
+fn savitzky_golay_weights(half: usize, poly_order: usize, eval_at: i32) -> Vec<f64> {
+    let terms = poly_order + 1;
+    let positions: Vec<f64> = (-(half as i32)..=half as i32).map(|p| p as f64).collect();
+
+    // normal equations (AᵀA) c = e, where e holds the powers of eval_at
+    let mut normal = vec![vec![0.0_f64; terms]; terms];
+    for (r, row) in normal.iter_mut().enumerate() {
+        for (c, val) in row.iter_mut().enumerate() {
+            *val = positions.iter().map(|p| p.powi((r + c) as i32)).sum();
+        }
+    }
+    let powers: Vec<f64> = (0..terms).map(|t| (eval_at as f64).powi(t as i32)).collect();
+    let coeffs = solve_linear(normal, powers);
+
+    positions
+        .iter()
+        .map(|p| coeffs.iter().enumerate().map(|(t, c)| c * p.powi(t as i32)).sum())
+        .collect()
+}
+
+/// Solve a small dense linear system with gaussian elimination and partial pivoting
+pub fn solve_linear(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|x, y| a[*x][col].abs().total_cmp(&a[*y][col].abs()))
+            .unwrap();
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        if a[col][col].abs() < f64::EPSILON {
+            continue;
+        }
+        let pivot_row = a[col].clone();
+        for row in col + 1..n {
+            let factor = a[row][col] / pivot_row[col];
+            for (val, pivot_val) in a[row][col..].iter_mut().zip(&pivot_row[col..]) {
+                *val -= factor * pivot_val;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = if a[row][row].abs() < f64::EPSILON { 0.0 } else { (b[row] - sum) / a[row][row] };
+    }
+    x
+}
+
+/// A single second order section
+struct Biquad {
+    b: [f32; 3],
+    a: [f32; 2],
+}
+
+impl Biquad {
+    fn low_pass(cutoff: f32, q: f32, sample_rate: f32) -> Biquad {
+        let w0 = 2.0 * PI * cutoff / sample_rate;
+        let alpha = w0.sin() / (2.0 * q);
+        let cos_w0 = w0.cos();
+        let a0 = 1.0 + alpha;
+
+        Biquad {
+            b: [(1.0 - cos_w0) / 2.0 / a0, (1.0 - cos_w0) / a0, (1.0 - cos_w0) / 2.0 / a0],
+            a: [-2.0 * cos_w0 / a0, (1.0 - alpha) / a0],
+        }
+    }
+
+    /// Filter the data in place, starting from a settled state at the first sample
+    fn run(&self, data: &mut [f32]) {
+        let (mut x1, mut x2) = (data[0], data[0]);
+        let (mut y1, mut y2) = (data[0], data[0]);
+
+        for d in data.iter_mut() {
+            let x0 = *d;
+            let y0 = self.b[0] * x0 + self.b[1] * x1 + self.b[2] * x2 - self.a[0] * y1 - self.a[1] * y2;
+            (x2, x1) = (x1, x0);
+            (y2, y1) = (y1, y0);
+            *d = y0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: f32 = 1000.0;
+
+    fn sine(frequency: f32, amplitude: f32) -> Vec<f32> {
+        (0..2000).map(|i| amplitude * (2.0 * PI * frequency * i as f32 / RATE).sin()).collect()
+    }
+
+    /// RMS of the middle of the data, away from the edge effects
+    fn middle_rms(data: &[f32]) -> f32 {
+        let middle = &data[data.len() / 4..data.len() * 3 / 4];
+        (middle.iter().map(|d| d * d).sum::<f32>() / middle.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn constant_passes_through_unchanged() {
+        let constant = vec![42.0; 500];
+
+        for filter in Filter::defaults() {
+            for filtered in filter.apply(&constant, RATE) {
+                assert!((filtered - 42.0).abs() < 1e-3, "{} gave {}", filter.name(), filtered);
+            }
+        }
+    }
+
+    #[test]
+    fn butterworth_attenuates_above_cutoff() {
+        // 4th order zero phase, so 8 poles worth of roll off 4 octaves above the cutoff
+        let high = butterworth(&sine(320.0, 10.0), 20.0, 4, RATE);
+        assert!(middle_rms(&high) < 0.01);
+
+        // well below the cutoff the sine comes through at the same size
+        let low = butterworth(&sine(2.0, 10.0), 20.0, 4, RATE);
+        assert!((middle_rms(&low) - 10.0 / 2.0_f32.sqrt()).abs() < 0.1);
+    }
+
+    #[test]
+    fn savitzky_golay_attenuates_high_frequency() {
+        let filtered = savitzky_golay(&sine(250.0, 10.0), 21, 3);
+        assert!(middle_rms(&filtered) < 1.0);
+    }
+
+    #[test]
+    fn savitzky_golay_preserves_polynomial_of_its_order() {
+        let cubic: Vec<f32> = (0..200).map(|i| {
+            let x = i as f32 / 100.0 - 1.0;
+            2.0 * x * x * x - x * x + 0.5 * x + 3.0
+        }).collect();
+
+        // including the ends, which are fitted with the nearest full window
+        for (filtered, expected) in savitzky_golay(&cubic, 21, 3).iter().zip(&cubic) {
+            assert!((filtered - expected).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn solve_linear_solves_pivoted_system() {
+        // the first pivot is zero so the rows have to be swapped
+        let a = vec![vec![0.0, 2.0, 1.0], vec![1.0, 1.0, 0.0], vec![3.0, 0.0, 1.0]];
+        let x = solve_linear(a, vec![7.0, 3.0, 6.0]);
+
+        for (solved, expected) in x.iter().zip([1.0, 2.0, 3.0]) {
+            assert!((solved - expected).abs() < 1e-9);
+        }
+    }
+}
@@ -6,8 +6,16 @@ pub mod bar_graph;
 pub mod line_manager;
 pub mod suspension_graph;
 pub mod disp_vel_graph;
+pub mod balance_graph;
+pub mod spectrum_graph;
+pub mod damper_graph;
+pub mod spring_graph;
+pub mod time_graph;
 pub mod wave_gen;
 
+/// Group the plots with time on the x axis are linked under, so they pan and zoom together
+pub const TIME_AXIS_ID: &str = "time_axis";
+
 /// Convert a value of an arbitrary data type to a PlotPoint
 pub trait ToPlotPoint {
     fn to_plot_point(&self) -> PlotPoint;
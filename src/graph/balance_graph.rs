@@ -0,0 +1,57 @@
+use egui::{Color32, Context, Id, Ui};
+use egui_plot::{Legend, Line, LineStyle, Plot, PlotPoints};
+
+use crate::data::{Data, TelemData};
+
+use super::Graph;
+
+/// Front travel percentiles plotted against the rear ones, with the balance coefficients from the stroke velocities
+pub struct BalanceGraph {
+    /// Field holding the (rear percentile, front percentile) pairs
+    curve_str: String,
+}
+
+impl BalanceGraph {
+    pub fn new(curve_str: String) -> BalanceGraph {
+        BalanceGraph { curve_str }
+    }
+}
+
+impl<'a> Graph<'a> for BalanceGraph {
+    fn draw(&self, data: &Data, _ctx: &Context, ui: &mut Ui) {
+        let curve: Vec<[f64; 2]> = match data.get(self.curve_str.clone()) {
+            Ok(TelemData::F32PV(points)) => points.iter().map(|(r, f)| [*r as f64, *f as f64]).collect(),
+            _ => return,
+        };
+
+        ui.vertical(|ui| {
+            let coefficient = |field: &str| {
+                data.get_f32_err(field.to_string()).map_or("-".to_string(), |c| format!("{:.2}", c))
+            };
+            ui.label(format!(
+                "balance (front / rear velocity, 1.00 is balanced)    compression: {}    rebound: {}",
+                coefficient("compression_balance"),
+                coefficient("rebound_balance")
+            ));
+
+            Plot::new("balance")
+                .id(Id::new(self.curve_str.clone()))
+                .width(500.0)
+                .height(500.0)
+                .data_aspect(1.0)
+                .allow_scroll(false)
+                .allow_boxed_zoom(false)
+                .include_x(0.0)
+                .include_x(100.0)
+                .include_y(0.0)
+                .include_y(100.0)
+                .x_axis_label("rear travel percentile (%)")
+                .y_axis_label("front travel percentile (%)")
+                .legend(Legend::default())
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new(PlotPoints::new(vec![[0.0, 0.0], [100.0, 100.0]])).style(LineStyle::dashed_loose()).color(Color32::GRAY).name("Ideal balance"));
+                    plot_ui.line(Line::new(PlotPoints::new(curve)).width(2.0).color(Color32::YELLOW).name("Front vs rear"));
+                });
+        });
+    }
+}
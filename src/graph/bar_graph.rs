@@ -1,59 +1,170 @@
 use crate::data::TelemData;
 use egui::{Color32, Id};
-use egui_plot::{uniform_grid_spacer, Axis, Plot};
+use egui_plot::{Legend, Plot};
 use egui_plot::{Bar, BarChart};
+use serde::{Deserialize, Serialize};
 
 use super::Graph;
-const BAR_WIDTH: f64 = 1.0;
 
+/// Opacity of the bars so overlaid histograms stay visible through each other
+const BAR_ALPHA: u8 = 110;
+
+/// What the height of a histogram bar shows
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Normalisation {
+    /// Number of samples in the bin
+    Count,
+    /// % of the analysed time spent in the bin
+    PercentTime,
+    /// Seconds spent in the bin
+    Seconds,
+}
+
+impl Normalisation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Normalisation::Count => "count",
+            Normalisation::PercentTime => "% time",
+            Normalisation::Seconds => "seconds",
+        }
+    }
+
+    /// Factor turning a sample count into this normalisation
+    ///
+    /// # Arguments
+    ///
+    /// `total`: The number of samples binned
+    /// `polling_rate`: Polling rate of the channel
+    pub fn scale(&self, total: usize, polling_rate: f32) -> f64 {
+        match self {
+            Normalisation::Count => 1.0,
+            Normalisation::PercentTime => 100.0 / usize::max(total, 1) as f64,
+            Normalisation::Seconds => 1.0 / polling_rate as f64,
+        }
+    }
+}
+
+/// Binning of a travel histogram
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct HistogramSettings {
+    pub bins: usize,
+    /// Start of the binned range in % of full travel
+    pub min: f32,
+    /// End of the binned range in % of full travel
+    pub max: f32,
+    pub normalisation: Normalisation,
+}
+
+impl Default for HistogramSettings {
+    fn default() -> Self {
+        HistogramSettings {
+            bins: 26,
+            min: 0.0,
+            max: 100.0,
+            normalisation: Normalisation::Count,
+        }
+    }
+}
+
+impl HistogramSettings {
+    /// The binned range in the unit of the travel
+    ///
+    /// # Arguments
+    ///
+    /// `full_travel`: The travel at full compression in the unit of the travel
+    pub fn range(&self, full_travel: f32) -> (f64, f64) {
+        ((self.min * full_travel / 100.0) as f64, (self.max * full_travel / 100.0) as f64)
+    }
+}
+
+/// One set of bin counts drawn on a histogram
+struct HistogramSeries {
+    count_str: String,
+    name: String,
+    colour: Color32,
+    /// (start, end) of the binned range, in the unit of the x axis
+    range: (f64, f64),
+    /// Factor applied to the counts to normalise them
+    scale: f64,
+}
+
+/// Histograms of one or more channels drawn on the same axes
 pub struct BarPoints {
-    sus_count_str: String,
+    id_str: String,
+    series: Vec<HistogramSeries>,
     dims: Option<(f32, f32)>,
-    colour: Color32
+    x_label: String,
+    y_label: String,
 }
 
 impl BarPoints {
-    pub fn new(sus_count_str: String, colour:Color32) -> BarPoints {
+    pub fn new(id_str: String) -> BarPoints {
         BarPoints {
-            sus_count_str,
+            id_str,
+            series: Vec::new(),
             dims: None,
-            colour: colour,
+            x_label: String::new(),
+            y_label: String::new(),
         }
     }
 
+    /// Adds a histogram to the plot
+    ///
+    /// # Arguments
+    ///
+    /// `count_str`: The field holding the bin counts
+    /// `name`: The name shown in the legend
+    /// `colour`: The bar colour, drawn partly transparent
+    /// `range`: (start, end) of the binned range on the x axis
+    /// `scale`: Factor applied to the counts, see `Normalisation::scale`
+    pub fn add_series(&mut self, count_str: String, name: String, colour: Color32, range: (f64, f64), scale: f64) {
+        self.series.push(HistogramSeries { count_str, name, colour, range, scale });
+    }
+
     pub fn set_dims(&mut self, width: f32, height: f32) {
         self.dims = Some((width, height));
     }
+
+    pub fn set_labels(&mut self, x_label: String, y_label: String) {
+        self.x_label = x_label;
+        self.y_label = y_label;
+    }
 }
 
 impl<'a> Graph<'a> for BarPoints {
     fn draw(&self, data: &crate::data::Data, _ctx: &egui::Context, ui: &mut egui::Ui) {
-        let data_count_res = data.get(self.sus_count_str.clone());
-        let data_count;
-        if let Ok(TelemData::U32V(counts)) = data_count_res {
-            data_count = counts.to_vec();
-        } else {
-            return;
-        }
+        let mut charts = Vec::new();
+        for series in &self.series {
+            let data_count = match data.get(series.count_str.clone()) {
+                Ok(TelemData::U32V(counts)) if !counts.is_empty() => counts,
+                _ => continue,
+            };
 
-        let mut bars = Vec::new();
-        for (i, v) in data_count.iter().enumerate() {
-            let bar = Bar::new(i as f64 * BAR_WIDTH, *v as f64).width(BAR_WIDTH).fill(self.colour);
+            let bar_width = (series.range.1 - series.range.0) / data_count.len() as f64;
+            let (r, g, b, _) = series.colour.to_tuple();
+            let fill = Color32::from_rgba_unmultiplied(r, g, b, BAR_ALPHA);
+            let bars = data_count.iter().enumerate().map(|(i, v)| {
+                Bar::new(series.range.0 + (i as f64 + 0.5) * bar_width, *v as f64 * series.scale).width(bar_width).fill(fill)
+            }).collect();
 
-            bars.push(bar);
+            charts.push(BarChart::new(bars).color(series.colour).name(series.name.clone()));
+        }
+        if charts.is_empty() {
+            return;
         }
-
-        let histogram = BarChart::new(bars);
 
         let mut plot = Plot::new("histogram")
-            .id(Id::new(self.sus_count_str.clone()))
+            .id(Id::new(self.id_str.clone()))
             .view_aspect(2.0)
             .allow_scroll(false)
             .allow_boxed_zoom(false)
             .allow_zoom(false)
             .allow_drag(false)
-            .show_grid(false);
-            
+            .show_grid(false)
+            .x_axis_label(self.x_label.clone())
+            .y_axis_label(self.y_label.clone())
+            .legend(Legend::default());
 
         if let Some((width, height)) = self.dims {
             plot = plot
@@ -61,6 +172,10 @@ impl<'a> Graph<'a> for BarPoints {
                 .height(height);
         }
 
-        plot.show(ui, |plot_ui| plot_ui.bar_chart(histogram));
+        plot.show(ui, |plot_ui| {
+            for chart in charts {
+                plot_ui.bar_chart(chart);
+            }
+        });
     }
 }
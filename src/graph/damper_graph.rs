@@ -0,0 +1,60 @@
+use egui::{Color32, Context, Id, Ui};
+use egui_plot::{Legend, Line, MarkerShape, Plot, PlotPoints, Points};
+
+use crate::data::{Data, TelemData};
+
+use super::Graph;
+
+/// Estimated damping force against shock velocity of both ends, compression up and to the right, rebound down and
+/// to the left
+pub struct DamperGraph {
+    /// Fields are `end` + "_damper_compression" and `end` + "_damper_rebound"
+    ends: Vec<(String, Color32)>,
+}
+
+impl DamperGraph {
+    pub fn new() -> DamperGraph {
+        DamperGraph {
+            ends: vec![("rear".to_string(), Color32::RED), ("front".to_string(), Color32::LIGHT_BLUE)],
+        }
+    }
+}
+
+impl<'a> Graph<'a> for DamperGraph {
+    fn draw(&self, data: &Data, _ctx: &Context, ui: &mut Ui) {
+        let mut curves = Vec::new();
+        for (end, colour) in &self.ends {
+            for direction in ["compression", "rebound"] {
+                if let Ok(TelemData::F32PV(curve)) = data.get(end.clone() + "_damper_" + direction) {
+                    let points: Vec<[f64; 2]> = curve.iter().map(|(v, f)| [*v as f64, *f as f64]).collect();
+                    curves.push((format!("{} {}", end, direction), points, *colour));
+                }
+            }
+        }
+
+        ui.vertical(|ui| {
+            if curves.iter().all(|(_, points, _)| points.is_empty()) {
+                ui.label("damper curve: set the springs and the shock strokes in mm to estimate it");
+                return;
+            }
+            ui.label("estimated damper curve, the spring force beyond static sag at the fastest point of each stroke");
+            ui.label("rebound is close, compression leaves out the load from the ground so is only a rough guide");
+
+            Plot::new("damper")
+                .id(Id::new("damper_curve"))
+                .width(1000.0)
+                .height(400.0)
+                .allow_scroll(false)
+                .allow_boxed_zoom(false)
+                .x_axis_label("shock velocity (mm/s)")
+                .y_axis_label("force (N)")
+                .legend(Legend::default())
+                .show(ui, |plot_ui| {
+                    for (name, points, colour) in curves {
+                        plot_ui.points(Points::new(PlotPoints::new(points.clone())).shape(MarkerShape::Circle).radius(3.0).color(colour).name(name.clone()));
+                        plot_ui.line(Line::new(PlotPoints::new(points)).width(2.0).color(colour).name(name));
+                    }
+                });
+        });
+    }
+}
@@ -1,11 +1,13 @@
-use egui::{Color32, Id, Vec2b};
-use egui_plot::{Line, Plot, PlotPoint, PlotPoints};
-use linreg::linear_regression_of;
+use egui::{Color32, Id};
+use egui_plot::{Legend, Line, LineStyle, Plot, PlotPoints, PlotUi};
 
 use crate::data::TelemData;
+use crate::regression::{self, LineFit};
 
 use super::{to_plot_points, Graph};
 
+/// Points along the fitted line, the confidence band curves so needs more than the two ends
+const BAND_STEPS: usize = 20;
 
 pub struct DispVelGraph{
     plot_id:String,
@@ -28,63 +30,69 @@ impl DispVelGraph {
 }   
 
 impl <'a> Graph<'a> for DispVelGraph{
-    fn draw(&self, data: &crate::data::Data, ctx: &egui::Context, ui: &mut egui::Ui) {
-
-
-        let front_res = data.get(self.front.clone());
-        let front_data;
-        if let Ok(TelemData::F32PV(counts)) = front_res {
-            front_data = counts.to_vec();
-        } else {
-            return;
-        }
-
-        let (mut front_slope,mut front_intercept):(f32,f32) = (0.0,0.0);
-        match linear_regression_of(&front_data) {
-            Ok(r) => (front_slope,front_intercept) = r,
-            Err(e) => eprintln!("lack of turning points"),
-        }
-        let front_reg_line = [[0.0_f64,front_intercept as f64],[100.0,100.0 * front_slope as f64]];
-            
-
-
-        let rear_res = data.get(self.rear.clone());
-        let rear_data;
-        if let Ok(TelemData::F32PV(counts)) = rear_res {
-            rear_data = counts.to_vec();
-        } else {
-            return;
-        }
-
-        let (rear_slope,rear_intercept):(f32,f32) = linear_regression_of(&rear_data).unwrap(); //(slope, intercept)
-        let rear_reg_line = [[0.0_f64,rear_intercept as f64],[100.0,100.0 * rear_slope as f64]];
-            
-        let _axis_bools_auto_zoom = Vec2b::new(false, false);
-        let mut max = 0.0;
-        for i in &front_data{
-            if i.1 > max{
-                max = i.1.clone();
-            }
-        }
-
-        let rebound_plot = Plot::new("disp_vel")
+    fn draw(&self, data: &crate::data::Data, _ctx: &egui::Context, ui: &mut egui::Ui) {
+        let front_data = match data.get(self.front.clone()) {
+            Ok(TelemData::F32PV(strokes)) => strokes,
+            _ => return,
+        };
+        let rear_data = match data.get(self.rear.clone()) {
+            Ok(TelemData::F32PV(strokes)) => strokes,
+            _ => return,
+        };
+        let front_fit = data.get_line_fit(self.front.clone() + "_fit");
+        let rear_fit = data.get_line_fit(self.rear.clone() + "_fit");
+
+        let max = front_data.iter().chain(rear_data.iter()).map(|(_, v)| *v).fold(0.0, f32::max);
+
+        let mut plot = Plot::new("disp_vel")
             .id(Id::new(&self.plot_id))
             .view_aspect(3.0)
             .allow_scroll(false)
             .allow_boxed_zoom(false)
-            .data_aspect(1.0/(max/50.0))
-            .show_grid(true);
-        ui.horizontal(|ui|{
-
-            rebound_plot.show(ui, |plot_ui| {
-                plot_ui.points(egui_plot::Points::new( PlotPoints::Owned( to_plot_points(&rear_data))).radius(4.0).color(Color32::RED));
-                plot_ui.line(Line::new(PlotPoints::new(rear_reg_line.to_vec())).width(3.0).color(Color32::RED));
-                plot_ui.points(egui_plot::Points::new( PlotPoints::Owned( to_plot_points(&front_data))).radius(4.0).color(Color32::LIGHT_BLUE));
-                plot_ui.line(Line::new(PlotPoints::new(front_reg_line.to_vec())).width(3.0).color(Color32::LIGHT_BLUE));
-            
+            .show_grid(true)
+            .legend(Legend::default());
+        if max > 0.0 {
+            plot = plot.data_aspect(1.0/(max/50.0));
+        }
+
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}    rear: {}    front: {}", self.plot_id, fit_summary(rear_fit, rear_data.len()), fit_summary(front_fit, front_data.len())));
             });
 
+            plot.show(ui, |plot_ui| {
+                for (name, strokes, fit, colour) in [("Rear", rear_data, rear_fit, Color32::RED), ("Front", front_data, front_fit, Color32::LIGHT_BLUE)] {
+                    plot_ui.points(egui_plot::Points::new( PlotPoints::Owned( to_plot_points(strokes))).radius(4.0).color(colour).name(name));
+                    if let Some(fit) = fit {
+                        draw_fit(plot_ui, fit, strokes, colour, name);
+                    }
+                }
+            });
         });
     }
 
-}
\ No newline at end of file
+}
+
+/// Slope and R² of a fit, or why there is none
+fn fit_summary(fit: Option<&LineFit>, count: usize) -> String {
+    match fit {
+        Some(fit) => format!("slope {:.2}/s, R² {:.2} ({} strokes)", fit.slope, fit.r_squared, fit.count),
+        None => format!("too few strokes to fit ({} of {})", count, regression::MIN_POINTS),
+    }
+}
+
+/// Draws the fitted line with its confidence band over the amplitudes of the strokes
+fn draw_fit(plot_ui: &mut PlotUi, fit: &LineFit, strokes: &[(f32, f32)], colour: Color32, name: &str) {
+    let x_min = strokes.iter().map(|(a, _)| *a).fold(f32::MAX, f32::min);
+    let x_max = strokes.iter().map(|(a, _)| *a).fold(f32::MIN, f32::max);
+    let xs: Vec<f32> = (0..=BAND_STEPS).map(|i| x_min + (x_max - x_min) * i as f32 / BAND_STEPS as f32).collect();
+
+    let curve = |offset: f32| -> Vec<[f64; 2]> {
+        xs.iter().map(|x| [*x as f64, (fit.predict(*x) + offset * fit.confidence(*x)) as f64]).collect()
+    };
+
+    plot_ui.line(Line::new(PlotPoints::new(curve(0.0))).width(3.0).color(colour).name(name.to_string() + " fit"));
+    for side in [-1.0, 1.0] {
+        plot_ui.line(Line::new(PlotPoints::new(curve(side))).style(LineStyle::dashed_loose()).color(colour).name(name.to_string() + " 95 % band"));
+    }
+}
@@ -22,6 +22,8 @@ pub struct LineManager {
     instances: Vec<LineInstance>,
     /// The polling rate of the data for the line
     polling_rate: f64,
+    /// Whether the points are `1 / polling_rate` apart in x, otherwise they are only sorted by x and are searched for
+    evenly_spaced: bool,
 }
 
 impl LineInstance {
@@ -57,6 +59,27 @@ impl LineInstance {
 
         (low_i, high_i)
     }
+
+    /// Find the start and end indices that fill a data range, for points sorted by x but not evenly spaced
+    ///
+    /// # Arguments
+    ///
+    /// `min`: The lowest visible x
+    /// `max`: The highest visible x
+    ///
+    /// # Returns
+    ///
+    /// A 2-tuple containing the start and end indices
+    pub fn get_points_in_x_range(&self, min: f64, max: f64) -> (usize, usize) {
+        if self.data.is_empty() {
+            return (0, 0);
+        }
+
+        let low_i = self.data.partition_point(|p| p.x < min).saturating_sub(1);
+        let high_i = usize::min(self.data.partition_point(|p| p.x <= max) + 1, self.data.len() - 1);
+
+        (low_i, high_i)
+    }
 }
 
 impl LineManager {
@@ -97,6 +120,24 @@ impl LineManager {
         LineManager {
             instances: instances,
             polling_rate,
+            evenly_spaced: true,
+        }
+    }
+
+    /// Create a new LineManager for points which are sorted by x but not evenly spaced, e.g. against distance
+    ///
+    /// # Arguments
+    ///
+    /// `data`: A vector containing the points to be plotted
+    /// `polling_rate`: The polling rate the points were sampled at
+    ///
+    /// # Returns
+    ///
+    /// A new LineManager
+    pub fn new_sorted(data: Vec<PlotPoint>, polling_rate: f64) -> LineManager {
+        LineManager {
+            evenly_spaced: false,
+            ..LineManager::new(data, polling_rate)
         }
     }
 
@@ -117,7 +158,11 @@ impl LineManager {
     /// None otherwise
     pub fn gen_line(&self, min: f64, max: f64) -> Option<Line> {
         for i in &self.instances {
-            let indices = i.get_points_in_range(min * self.polling_rate, max * self.polling_rate);
+            let indices = if self.evenly_spaced {
+                i.get_points_in_range(min * self.polling_rate, max * self.polling_rate)
+            } else {
+                i.get_points_in_x_range(min, max)
+            };
             let line_len = indices.1 - indices.0;
 
             if indices.1 - indices.0 > MAX_POINTS {
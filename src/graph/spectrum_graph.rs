@@ -0,0 +1,126 @@
+use std::cell::RefCell;
+
+use egui::{Color32, ColorImage, Context, Id, TextureHandle, TextureOptions, Ui};
+use egui_plot::{Line, Plot, PlotImage, PlotPoint, PlotPoints};
+
+use crate::data::{Data, TelemData};
+use crate::spectrum::Spectrogram;
+
+use super::Graph;
+
+/// Range in dB below the loudest bin the spectrogram colours cover, anything quieter is drawn black
+const SPECTROGRAM_RANGE_DB: f32 = 60.0;
+
+/// A spectrum of a channel against frequency, next to a spectrogram of how it changes over time
+pub struct SpectrumGraph {
+    /// Field holding the (frequency, power or amplitude) pairs
+    spectrum_str: String,
+    /// Field holding the spectrogram
+    spectrogram_str: String,
+    /// Label of the spectrum axis, the values are plotted in dB of this
+    spectrum_label: String,
+    /// The spectrum holds amplitudes rather than powers, so it is 20 log10 rather than 10 log10 in dB
+    amplitude: bool,
+    /// The spectrogram image, made the first time the graph is drawn as it needs the context
+    texture: RefCell<Option<TextureHandle>>,
+}
+
+impl SpectrumGraph {
+    pub fn new(spectrum_str: String, spectrogram_str: String, spectrum_label: String, amplitude: bool) -> SpectrumGraph {
+        SpectrumGraph {
+            spectrum_str,
+            spectrogram_str,
+            spectrum_label,
+            amplitude,
+            texture: RefCell::new(None),
+        }
+    }
+
+    /// Colour the spectrogram, time runs left to right and frequency bottom to top
+    fn spectrogram_image(spectrogram: &Spectrogram) -> ColorImage {
+        let (width, height) = (spectrogram.times.len(), spectrogram.frequencies.len());
+        let max_db = spectrogram.power.iter().flatten().map(|p| to_db(*p)).fold(f32::MIN, f32::max);
+
+        let mut pixels = vec![Color32::BLACK; width * height];
+        for (x, column) in spectrogram.power.iter().enumerate() {
+            for (bin, power) in column.iter().enumerate() {
+                let level = 1.0 - (max_db - to_db(*power)) / SPECTROGRAM_RANGE_DB;
+                pixels[(height - 1 - bin) * width + x] = colour_map(level.clamp(0.0, 1.0));
+            }
+        }
+
+        ColorImage { size: [width, height], pixels }
+    }
+}
+
+impl<'a> Graph<'a> for SpectrumGraph {
+    fn draw(&self, data: &Data, ctx: &Context, ui: &mut Ui) {
+        // an amplitude squared is a power, so its dB are twice as many
+        let db_scale = if self.amplitude { 2.0 } else { 1.0 };
+        let spectrum: Vec<[f64; 2]> = match data.get(self.spectrum_str.clone()) {
+            // the DC bin is only what is left after removing the mean
+            Ok(TelemData::F32PV(points)) => points.iter().skip(1).map(|(f, p)| [*f as f64, (db_scale * to_db(*p)) as f64]).collect(),
+            _ => return,
+        };
+
+        Plot::new("spectrum")
+            .id(Id::new(self.spectrum_str.clone()))
+            .width(500.0)
+            .height(300.0)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .x_axis_label("frequency (Hz)")
+            .y_axis_label(format!("{} (dB)", self.spectrum_label))
+            .show(ui, |plot_ui| {
+                plot_ui.line(Line::new(PlotPoints::new(spectrum)).color(Color32::LIGHT_BLUE));
+            });
+
+        let spectrogram = match data.get_spectrogram(self.spectrogram_str.clone()) {
+            Some(s) if !s.times.is_empty() => s,
+            _ => return,
+        };
+
+        let mut texture = self.texture.borrow_mut();
+        let texture = texture.get_or_insert_with(|| {
+            ctx.load_texture(self.spectrogram_str.clone(), SpectrumGraph::spectrogram_image(spectrogram), TextureOptions::NEAREST)
+        });
+
+        // each column covers the time to the next segment, the bins run from 0 Hz to the Nyquist frequency
+        let hop = match spectrogram.times.as_slice() {
+            [first, second, ..] => second - first,
+            _ => 1.0,
+        } as f64;
+        let (start, end) = (spectrogram.times[0] as f64 - hop / 2.0, spectrogram.times[spectrogram.times.len() - 1] as f64 + hop / 2.0);
+        let max_frequency = spectrogram.frequencies.last().copied().unwrap_or(0.0) as f64;
+
+        Plot::new("spectrogram")
+            .id(Id::new(self.spectrogram_str.clone()))
+            .width(500.0)
+            .height(300.0)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .x_axis_label("time (s)")
+            .y_axis_label("frequency (Hz)")
+            .show(ui, |plot_ui| {
+                let centre = PlotPoint::new((start + end) / 2.0, max_frequency / 2.0);
+                plot_ui.image(PlotImage::new(texture.id(), centre, [(end - start) as f32, max_frequency as f32]));
+            });
+    }
+}
+
+/// A power in dB
+fn to_db(power: f32) -> f32 {
+    10.0 * f32::max(power, f32::MIN_POSITIVE).log10()
+}
+
+/// Dark blue through green to yellow for levels from 0 to 1
+fn colour_map(level: f32) -> Color32 {
+    const STOPS: [(f32, f32, f32); 4] = [(0.0, 0.0, 0.0), (20.0, 40.0, 140.0), (30.0, 170.0, 110.0), (250.0, 230.0, 30.0)];
+
+    let position = level * (STOPS.len() - 1) as f32;
+    let i = usize::min(position as usize, STOPS.len() - 2);
+    let t = position - i as f32;
+    let (a, b) = (STOPS[i], STOPS[i + 1]);
+    let mix = |x: f32, y: f32| (x + (y - x) * t) as u8;
+    Color32::from_rgb(mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
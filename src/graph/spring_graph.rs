@@ -0,0 +1,62 @@
+use egui::{Color32, Context, Id, Ui};
+use egui_plot::{Bar, BarChart, Legend, Line, Plot, PlotPoints};
+
+use crate::data::{Data, TelemData};
+
+use super::Graph;
+
+/// Share of the top of the plot the tallest histogram bar reaches, keeps the bars under the top of the curve
+const HISTOGRAM_HEIGHT: f64 = 0.5;
+
+/// Spring force against shock stroke for one end, with the time spent at each stroke drawn underneath
+pub struct SpringGraph {
+    /// "rear" or "front", the fields are `end` + "_spring_curve" and `end` + "_spring_counts"
+    end: String,
+    colour: Color32,
+}
+
+impl SpringGraph {
+    pub fn new(end: String, colour: Color32) -> SpringGraph {
+        SpringGraph { end, colour }
+    }
+}
+
+impl<'a> Graph<'a> for SpringGraph {
+    fn draw(&self, data: &Data, _ctx: &Context, ui: &mut Ui) {
+        let curve = match data.get(self.end.clone() + "_spring_curve") {
+            Ok(TelemData::F32PV(curve)) if !curve.is_empty() => curve,
+            _ => return,
+        };
+        let counts = match data.get(self.end.clone() + "_spring_counts") {
+            Ok(TelemData::U32V(counts)) => counts.as_slice(),
+            _ => &[],
+        };
+
+        // the histogram has no unit on the force axis so it is scaled to sit under the curve
+        let full_stroke = curve[curve.len() - 1].0 as f64;
+        let max_force = curve.iter().map(|(_, f)| *f).fold(0.0, f32::max) as f64;
+        let max_count = counts.iter().copied().max().unwrap_or(0);
+        let bar_width = full_stroke / usize::max(counts.len(), 1) as f64;
+        let (r, g, b, _) = self.colour.to_tuple();
+        let bars = counts.iter().enumerate().map(|(i, c)| {
+            let height = *c as f64 / u32::max(max_count, 1) as f64 * max_force * HISTOGRAM_HEIGHT;
+            Bar::new((i as f64 + 0.5) * bar_width, height).width(bar_width).fill(Color32::from_rgba_unmultiplied(r, g, b, 80))
+        }).collect();
+
+        Plot::new("spring")
+            .id(Id::new(self.end.clone() + "_spring"))
+            .width(500.0)
+            .height(300.0)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .include_y(0.0)
+            .x_axis_label(self.end.clone() + " shock stroke (mm)")
+            .y_axis_label("spring force (N)")
+            .legend(Legend::default())
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(BarChart::new(bars).color(self.colour).name("time at stroke (scaled)"));
+                let points: Vec<[f64; 2]> = curve.iter().map(|(s, f)| [*s as f64, *f as f64]).collect();
+                plot_ui.line(Line::new(PlotPoints::new(points)).width(2.0).color(self.colour).name("spring force"));
+            });
+    }
+}
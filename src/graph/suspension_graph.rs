@@ -1,15 +1,31 @@
-use egui::{Context, Id, Ui, Vec2b};
-use egui_plot::{Legend, Line, Plot, PlotBounds, PlotMemory, PlotPoints, Points};
+use egui::{Color32, Context, Id, PointerButton, Ui, Vec2b};
+use egui_plot::{HLine, Legend, Line, LineStyle, MarkerShape, Plot, PlotBounds, PlotMemory, PlotPoint, PlotPoints, Points, Polygon, Text, VLine};
 
 use crate::{
     data::{Data, TelemData},
-    graph::Graph,
+    distance::XAxis,
+    graph::{Graph, TIME_AXIS_ID},
+    travel_events::TravelEventKind,
 };
 
+/// Id the requested zoom range is stored under in the egui memory
+const ZOOM_REQUEST_ID: &str = "suspension_zoom_request";
+/// Id the x a range selection drag started at is stored under in the egui memory
+const SELECTION_START_ID: &str = "suspension_selection_start";
+/// Id a finished range selection is stored under in the egui memory until the app takes it
+const SELECTION_ID: &str = "suspension_selection";
+
 /// A graph that can be used to visualise suspension data
 pub struct SuspensionGraph {
     rear_sus_str: String,
     front_sus_str: String,
+    /// Fields holding the filtered copies of the travel lines, drawn on top of the raw lines
+    filtered_sus_strs: Option<(String, String)>,
+    /// The full travel, so the graph always shows the whole range
+    travel_max: f64,
+    travel_unit: String,
+    /// Whether the travel is plotted against time or distance, the events and regions are converted to match
+    x_axis: XAxis,
 }
 
 impl SuspensionGraph {
@@ -17,17 +33,49 @@ impl SuspensionGraph {
         SuspensionGraph {
             rear_sus_str,
             front_sus_str,
+            filtered_sus_strs: None,
+            travel_max: 100.0,
+            travel_unit: "%".to_string(),
+            x_axis: XAxis::Time,
         }
     }
+
+    /// Ask the suspension graph to show the time range between `min` and `max` the next time it is drawn
+    pub fn request_zoom(ctx: &Context, min: f64, max: f64) {
+        ctx.data_mut(|d| d.insert_temp(Id::new(ZOOM_REQUEST_ID), (min, max)));
+        ctx.request_repaint();
+    }
+
+    /// Take the time range the user selected by dragging with the secondary mouse button, if they finished one
+    /// since the last call
+    pub fn take_selection(ctx: &Context) -> Option<(f64, f64)> {
+        ctx.data_mut(|d| d.remove_temp::<(f64, f64)>(Id::new(SELECTION_ID)))
+    }
+
+    pub fn set_travel_range(&mut self, travel_max: f64, travel_unit: String) {
+        self.travel_max = travel_max;
+        self.travel_unit = travel_unit;
+    }
+
+    pub fn set_x_axis(&mut self, x_axis: XAxis) {
+        self.x_axis = x_axis;
+    }
+
+    /// A shaded region covering the full travel between two x values, without an outline
+    fn time_region(&self, start: f64, end: f64, colour: Color32) -> Polygon {
+        let corners = vec![[start, 0.0], [end, 0.0], [end, self.travel_max], [start, self.travel_max]];
+        Polygon::new(PlotPoints::new(corners)).fill_color(colour).stroke((0.0, colour))
+    }
+
+    pub fn set_filtered_lines(&mut self, rear_filtered_str: String, front_filtered_str: String) {
+        self.filtered_sus_strs = Some((rear_filtered_str, front_filtered_str));
+    }
 }
 
 impl<'a> Graph<'a> for SuspensionGraph {
     fn draw(&self, data: &Data, ctx: &Context, ui: &mut Ui) {
         let rear_line_manager_res = data.get(self.rear_sus_str.clone());
         let front_line_manager_res = data.get(self.front_sus_str.clone());
-        //let bottom_out_threshold_res = data.get("bottom_out_threshold".to_string());
-        let line_manager_res = data.get("suspension_line".to_string());
-        let bottom_out_threshold_res = data.get("bottom_out_threshold".to_string());
         let turning_points_res = data.get("rear_turning".to_string());
 
         let mut rear_line_manager = None;
@@ -39,14 +87,104 @@ impl<'a> Graph<'a> for SuspensionGraph {
             front_line_manager = Some(lm);
         }
 
-        // let mut bottom_out_threshold = 0.0;
-        // if let Ok(TelemData::F64(bot)) = bottom_out_threshold_res {
-        //     bottom_out_threshold = *bot;
-        // }
+        // the events and regions are timed, put them at the distance they happened at when plotting against distance
+        let distance_map = match self.x_axis {
+            XAxis::Distance => data.get_distance_map("distance_map".to_string()),
+            XAxis::Time => None,
+        };
+        let to_x = |time: f64| distance_map.map_or(time, |m| m.distance_at(time as f32) as f64);
+        let to_time = |x: f64| distance_map.map_or(x, |m| m.time_at(x as f32) as f64);
+
+        let mut threshold_lines = Vec::new();
+        let mut event_markers = Vec::new();
+        for (end, name) in [("rear", "Rear"), ("front", "Front")] {
+            for (threshold_field, threshold_name) in [("_bottom_out_threshold", " Bottom Out Threshold"), ("_top_out_threshold", " Top Out Threshold")] {
+                if let Some(threshold) = data.get_f32_err(end.to_string() + threshold_field) {
+                    threshold_lines.push(HLine::new(threshold).name(name.to_string() + threshold_name).style(LineStyle::dashed_loose()));
+                }
+            }
+
+            if let Some(events) = data.get_travel_events(end.to_string() + "_travel_events") {
+                for (kind, kind_name, shape) in [(TravelEventKind::BottomOut, " Bottom Outs", MarkerShape::Down), (TravelEventKind::TopOut, " Top Outs", MarkerShape::Up)] {
+                    let peaks: Vec<PlotPoint> = events.iter()
+                        .filter(|e| e.kind == kind)
+                        .map(|e| PlotPoint::new(to_x(e.peak_time as f64), e.peak))
+                        .collect();
+                    event_markers.push(Points::new(PlotPoints::Owned(peaks)).shape(shape).radius(6.0).filled(true).name(name.to_string() + kind_name));
+                }
+            }
+        }
+
+        // shade the riding and idle segments behind the travel lines
+        let mut segment_regions = Vec::new();
+        if let Some(segments) = data.get_activity_segments("activity_segments".to_string()) {
+            for segment in segments {
+                let (start, end) = (to_x(segment.start as f64), to_x(segment.end as f64));
+                let (name, colour) = match segment.riding {
+                    true => ("Riding", Color32::from_rgba_unmultiplied(0, 160, 0, 20)),
+                    false => ("Idle", Color32::from_rgba_unmultiplied(128, 128, 128, 40)),
+                };
+                segment_regions.push(self.time_region(start, end, colour).name(name));
+            }
+        }
+
+        let mut range_lines = Vec::new();
+        if let (Some(start), Some(end)) = (data.get_f32_err("analysis_start".to_string()), data.get_f32_err("analysis_end".to_string())) {
+            for time in [start, end] {
+                range_lines.push(VLine::new(to_x(time as f64)).name("Analysis Range").color(Color32::YELLOW).style(LineStyle::dashed_loose()));
+            }
+        }
+
+        let mut section_regions = Vec::new();
+        let mut marker_lines = Vec::new();
+        let mut annotation_labels = Vec::new();
+        if let Some(annotations) = data.get_annotations("annotations".to_string()) {
+            let colour = Color32::from_rgba_unmultiplied(80, 120, 255, 30);
+            for section in &annotations.sections {
+                let (start, end) = (to_x(section.start as f64), to_x(section.end as f64));
+                section_regions.push(self.time_region(start, end, colour).stroke((1.0, colour)).name("Sections"));
+                annotation_labels.push(Text::new(PlotPoint::new((start + end) / 2.0, self.travel_max), section.name.clone()));
+            }
+            for marker in &annotations.markers {
+                let x = to_x(marker.time as f64);
+                marker_lines.push(VLine::new(x).color(Color32::LIGHT_GRAY).name("Markers"));
+                annotation_labels.push(Text::new(PlotPoint::new(x, self.travel_max), marker.name.clone()));
+            }
+        }
+
+        let mut jump_regions = Vec::new();
+        if let Some(jumps) = data.get_jumps("jumps".to_string()) {
+            let colour = Color32::from_rgba_unmultiplied(255, 140, 0, 50);
+            for jump in jumps {
+                let (start, end) = (to_x(jump.take_off as f64), to_x(jump.landing as f64));
+                jump_regions.push(self.time_region(start, end, colour).stroke((1.0, colour)).name("Jumps"));
+                annotation_labels.push(Text::new(PlotPoint::new((start + end) / 2.0, self.travel_max * 0.9), format!("{:.2} s", jump.air_time)));
+            }
+        }
+
+        let mut braking_regions = Vec::new();
+        if let Some(events) = data.get_braking_events("braking_events".to_string()) {
+            let colour = Color32::from_rgba_unmultiplied(255, 0, 80, 40);
+            for event in events {
+                braking_regions.push(self.time_region(to_x(event.start as f64), to_x(event.end as f64), colour).name("Braking"));
+            }
+        }
+
+        let mut pack_down_regions = Vec::new();
+        for end in ["rear", "front"] {
+            if let Some(pack_downs) = data.get_pack_downs(end.to_string() + "_pack_downs") {
+                let colour = Color32::from_rgba_unmultiplied(160, 0, 255, 40);
+                for pack_down in pack_downs {
+                    let (start, end_time) = (to_x(pack_down.start as f64), to_x(pack_down.end as f64));
+                    pack_down_regions.push(self.time_region(start, end_time, colour).name("Pack Down"));
+                    annotation_labels.push(Text::new(PlotPoint::new((start + end_time) / 2.0, self.travel_max * 0.8), format!("{} +{:.0} %", end, pack_down.drift)));
+                }
+            }
+        }
 
         let mut turning_points = None;
         if let Ok(TelemData::PlotPointV(pts)) = turning_points_res {
-            turning_points = Some(pts);
+            turning_points = Some(pts.iter().map(|p| PlotPoint::new(to_x(p.x), p.y)).collect::<Vec<_>>());
         }
 
         let axis_bools_drag = Vec2b::new(true, false);
@@ -61,9 +199,12 @@ impl<'a> Graph<'a> for SuspensionGraph {
             .allow_boxed_zoom(false)
             .allow_drag(axis_bools_drag)
             .allow_zoom(axis_bools_drag)
+            .link_axis(Id::new(TIME_AXIS_ID), true, false)
             .show_grid(false)
             .include_y(0.0)
-            .include_y(100.0)
+            .include_y(self.travel_max)
+            .x_axis_label(self.x_axis.label())
+            .y_axis_label(format!("travel ({})", self.travel_unit))
             .legend(legend);
             //.include_y(data.get_f32("suspension_min".to_string()))
             //.include_y(data.get_f32("suspension_max".to_string()));
@@ -76,12 +217,6 @@ impl<'a> Graph<'a> for SuspensionGraph {
             extremes = [bounds.min()[0], bounds.max()[0]];
         }
 
-        // let bottom_out_points: PlotPoints = (0..2)
-        //     .map(|i| [i as f64 * extremes[1], bottom_out_threshold])
-        //     .collect();
-
-        // let bottom_out_line = Line::new(bottom_out_points);
-
         let mut rear_travel_line = None;
         let mut front_travel_line = None;
         
@@ -98,17 +233,97 @@ impl<'a> Graph<'a> for SuspensionGraph {
             );
         }
 
+        let mut filtered_travel_lines = Vec::new();
+        if let Some((rear_filtered_str, front_filtered_str)) = &self.filtered_sus_strs {
+            for (field, name) in [(rear_filtered_str, "Rear Suspension (filtered)"), (front_filtered_str, "Front Suspension (filtered)")] {
+                if let Ok(TelemData::LineManager(lm)) = data.get(field.clone()) {
+                    if let Some(line) = lm.gen_line(extremes[0], extremes[1]) {
+                        filtered_travel_lines.push(line.name(name));
+                    }
+                }
+            }
+        }
+
+        let zoom_request = ctx.data_mut(|d| d.remove_temp::<(f64, f64)>(Id::new(ZOOM_REQUEST_ID)))
+            .map(|(min, max)| (to_x(min), to_x(max)));
+        let selection_start = ctx.data(|d| d.get_temp::<f64>(Id::new(SELECTION_START_ID)));
+        let press_origin = ctx.input(|i| i.pointer.press_origin());
+
         plot.show(ui, |plot_ui| {
+            if let Some((min, max)) = zoom_request {
+                let bounds = plot_ui.plot_bounds();
+                plot_ui.set_plot_bounds(PlotBounds::from_min_max([min, bounds.min()[1]], [max, bounds.max()[1]]));
+            }
+            for region in segment_regions {
+                plot_ui.polygon(region);
+            }
+            for region in section_regions {
+                plot_ui.polygon(region);
+            }
+            for region in jump_regions {
+                plot_ui.polygon(region);
+            }
+            for region in braking_regions {
+                plot_ui.polygon(region);
+            }
+            for region in pack_down_regions {
+                plot_ui.polygon(region);
+            }
+            for range_line in range_lines {
+                plot_ui.vline(range_line);
+            }
+            for marker_line in marker_lines {
+                plot_ui.vline(marker_line);
+            }
+            for label in annotation_labels {
+                plot_ui.text(label);
+            }
+
+            // dragging with the secondary button selects a time range, the primary button still pans
+            let response = plot_ui.response().clone();
+            if response.drag_started_by(PointerButton::Secondary) {
+                if let Some(origin) = press_origin {
+                    let start = plot_ui.plot_from_screen(origin).x;
+                    ctx.data_mut(|d| d.insert_temp(Id::new(SELECTION_START_ID), start));
+                }
+            }
+            if let (Some(start), Some(pointer)) = (selection_start, plot_ui.pointer_coordinate()) {
+                if response.dragged_by(PointerButton::Secondary) {
+                    let colour = Color32::from_rgba_unmultiplied(255, 255, 0, 30);
+                    plot_ui.polygon(self.time_region(start, pointer.x, colour));
+                }
+                if response.drag_released_by(PointerButton::Secondary) {
+                    let selection = (to_time(f64::min(start, pointer.x).max(0.0)), to_time(f64::max(start, pointer.x)));
+                    ctx.data_mut(|d| {
+                        d.remove::<f64>(Id::new(SELECTION_START_ID));
+                        d.insert_temp(Id::new(SELECTION_ID), selection);
+                    });
+                    ctx.request_repaint();
+                }
+            }
+            // a drag released away from the plot is never seen as released by it, forget its start so the next
+            // drag does not begin there
+            if selection_start.is_some() && !ctx.input(|i| i.pointer.secondary_down()) {
+                ctx.data_mut(|d| d.remove::<f64>(Id::new(SELECTION_START_ID)));
+            }
             if let Some(travel_line_u) = rear_travel_line {
                 plot_ui.line(travel_line_u.name("Rear Suspension"));
             }
             if let Some(travel_line_u) = front_travel_line {
                 plot_ui.line(travel_line_u.name("Front Suspension"));
             }
+            for filtered_line in filtered_travel_lines {
+                plot_ui.line(filtered_line);
+            }
             if let Some(turning_points_u) = turning_points {
-                plot_ui.points(Points::new(PlotPoints::Owned(turning_points_u.clone())).radius(3.0));
+                plot_ui.points(Points::new(PlotPoints::Owned(turning_points_u)).radius(3.0));
+            }
+            for threshold_line in threshold_lines {
+                plot_ui.hline(threshold_line);
+            }
+            for markers in event_markers {
+                plot_ui.points(markers);
             }
-            //plot_ui.line(bottom_out_line);  
         });
     }
 }
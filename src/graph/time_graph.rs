@@ -0,0 +1,73 @@
+use egui::{Color32, Context, Id, Ui};
+use egui_plot::{Legend, Plot, PlotMemory};
+
+use crate::data::{Data, TelemData};
+use crate::distance::XAxis;
+
+use super::{Graph, TIME_AXIS_ID};
+
+/// Lines of channels against time or distance, the x axis follows the suspension graph
+pub struct TimeGraph {
+    id_str: String,
+    /// (field holding the LineManager, legend name, colour) of each line
+    lines: Vec<(String, String, Color32)>,
+    y_label: String,
+    x_axis: XAxis,
+}
+
+impl TimeGraph {
+    pub fn new(id_str: String, y_label: String) -> TimeGraph {
+        TimeGraph {
+            id_str,
+            lines: Vec::new(),
+            y_label,
+            x_axis: XAxis::Time,
+        }
+    }
+
+    /// Set what the lines are plotted against, only labels the axis as the lines are stored against it
+    pub fn set_x_axis(&mut self, x_axis: XAxis) {
+        self.x_axis = x_axis;
+    }
+
+    pub fn add_line(&mut self, line_str: String, name: String, colour: Color32) {
+        self.lines.push((line_str, name, colour));
+    }
+}
+
+impl<'a> Graph<'a> for TimeGraph {
+    fn draw(&self, data: &Data, ctx: &Context, ui: &mut Ui) {
+        if self.lines.iter().all(|(field, _, _)| data.get(field.clone()).is_err()) {
+            return;
+        }
+
+        let mut extremes = [0.0, 0.0];
+        if let Some(state) = PlotMemory::load(ctx, Id::new(self.id_str.clone())) {
+            let transform = state.transform();
+            let bounds = transform.bounds();
+            extremes = [bounds.min()[0], bounds.max()[0]];
+        }
+
+        let lines: Vec<_> = self.lines.iter()
+            .filter_map(|(field, name, colour)| match data.get(field.clone()) {
+                Ok(TelemData::LineManager(lm)) => lm.gen_line(extremes[0], extremes[1]).map(|l| l.name(name).color(*colour)),
+                _ => None,
+            })
+            .collect();
+
+        Plot::new(self.id_str.clone())
+            .id(Id::new(self.id_str.clone()))
+            .view_aspect(5.0)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .link_axis(Id::new(TIME_AXIS_ID), true, false)
+            .x_axis_label(self.x_axis.label())
+            .y_axis_label(self.y_label.clone())
+            .legend(Legend::default())
+            .show(ui, |plot_ui| {
+                for line in lines {
+                    plot_ui.line(line);
+                }
+            });
+    }
+}
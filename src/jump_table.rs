@@ -0,0 +1,84 @@
+use egui::Context;
+
+use crate::data::Data;
+use crate::graph::suspension_graph::SuspensionGraph;
+use crate::jumps::JumpDetector;
+
+/// Time either side of a jump shown when zooming to it
+const ZOOM_PADDING: f64 = 0.5;
+
+/// Window listing the jumps of the run, clicking a take-off time zooms the suspension graph to the jump
+pub struct JumpTable {
+    pub open: bool,
+}
+
+impl JumpTable {
+    pub fn new() -> JumpTable {
+        JumpTable { open: false }
+    }
+
+    /// Draw the window
+    ///
+    /// # Arguments
+    ///
+    /// `ctx`: The eGui context
+    /// `data`: The run data holding the "jumps" field
+    /// `detector`: The jump detector settings from the config
+    ///
+    /// # Returns
+    ///
+    /// true if the detector settings were applied and the data needs to be recalculated
+    pub fn update(&mut self, ctx: &Context, data: &Data, detector: &mut JumpDetector) -> bool {
+        let mut open = self.open;
+        let mut changed = false;
+
+        egui::Window::new("Jumps").open(&mut open).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut detector.free_fall_g).clamp_range(0.0..=1.0).speed(0.01).prefix("free fall: ").suffix(" g"));
+                ui.add(egui::DragValue::new(&mut detector.max_air_travel).clamp_range(0.0..=100.0).speed(0.1).prefix("max air travel: ").suffix(" %"));
+                ui.add(egui::DragValue::new(&mut detector.min_landing_travel).clamp_range(0.0..=100.0).speed(0.1).prefix("min landing travel: ").suffix(" %"));
+            });
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut detector.min_air_time).clamp_range(0.0..=10.0).speed(0.01).prefix("min air time: ").suffix(" s"));
+                ui.add(egui::DragValue::new(&mut detector.landing_window).clamp_range(0.0..=2.0).speed(0.01).prefix("landing window: ").suffix(" s"));
+                if ui.button("Detect jumps").clicked() {
+                    changed = true;
+                }
+            });
+
+            ui.separator();
+
+            let jumps = match data.get_jumps("jumps".to_string()) {
+                Some(j) => j,
+                None => {
+                    ui.label("Jumps need the IMU columns and mapped travel");
+                    return;
+                }
+            };
+            ui.label(format!("{} jumps", jumps.len()));
+
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                egui::Grid::new("jump_grid").striped(true).show(ui, |ui| {
+                    for heading in ["take-off (s)", "air time (s)", "landing (g)", "rear travel (%)", "front travel (%)"] {
+                        ui.label(heading);
+                    }
+                    ui.end_row();
+
+                    for jump in jumps {
+                        if ui.link(format!("{:.3}", jump.take_off)).clicked() {
+                            SuspensionGraph::request_zoom(ctx, jump.take_off as f64 - ZOOM_PADDING, (jump.landing + detector.landing_window) as f64 + ZOOM_PADDING);
+                        }
+                        ui.label(format!("{:.3}", jump.air_time));
+                        ui.label(format!("{:.1}", jump.landing_peak_g));
+                        ui.label(format!("{:.1}", jump.rear_landing_travel));
+                        ui.label(format!("{:.1}", jump.front_landing_travel));
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+
+        self.open = open;
+        changed
+    }
+}
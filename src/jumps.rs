@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+use crate::filter::moving_average;
+use crate::loader::ImuData;
+
+/// Standard gravity in m/s², the IMU reports acceleration in m/s²
+pub const GRAVITY: f32 = 9.81;
+/// Time in seconds the acceleration magnitude is averaged over, so vibration does not break up a free fall
+const SMOOTHING_TIME: f32 = 0.02;
+
+/// A jump, from leaving the ground to the suspension taking the landing
+#[derive(Clone, Copy, Debug)]
+pub struct Jump {
+    /// Time the bike left the ground in seconds
+    pub take_off: f32,
+    /// Time the bike touched down in seconds
+    pub landing: f32,
+    /// Time in the air in seconds
+    pub air_time: f32,
+    /// Highest acceleration while landing in g, the accelerometer saturates so big landings read low
+    pub landing_peak_g: f32,
+    /// Deepest rear travel while landing in %
+    pub rear_landing_travel: f32,
+    /// Deepest front travel while landing in %
+    pub front_landing_travel: f32,
+}
+
+/// Finds jumps, which show up as a spell of near zero g with both ends extended followed by a big compression
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct JumpDetector {
+    /// Acceleration magnitude in g below which the bike counts as falling freely
+    pub free_fall_g: f32,
+    /// Both ends have to be extended past this travel in % while in the air
+    pub max_air_travel: f32,
+    /// Shortest time in the air in seconds that counts as a jump
+    pub min_air_time: f32,
+    /// Time after touching down in seconds the landing peaks are searched for in
+    pub landing_window: f32,
+    /// One end has to compress past this travel in % while landing
+    pub min_landing_travel: f32,
+}
+
+impl Default for JumpDetector {
+    fn default() -> JumpDetector {
+        JumpDetector {
+            free_fall_g: 0.4,
+            max_air_travel: 10.0,
+            min_air_time: 0.1,
+            landing_window: 0.3,
+            min_landing_travel: 20.0,
+        }
+    }
+}
+
+impl JumpDetector {
+    /// Find the jumps in a run
+    ///
+    /// # Arguments
+    ///
+    /// `imu`: The IMU data of the run
+    /// `rear`: (rear travel in %, polling rate)
+    /// `front`: (front travel in %, polling rate)
+    ///
+    /// # Returns
+    ///
+    /// The jumps in time order
+    pub fn detect(&self, imu: &ImuData, rear: (&[f32], f32), front: (&[f32], f32)) -> Vec<Jump> {
+        let imu_rate = imu.polling_rate as f32;
+        let magnitudes: Vec<f32> = imu.accel.iter().map(|a| (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt() / GRAVITY).collect();
+        let smoothed = moving_average(&magnitudes, usize::max((SMOOTHING_TIME * imu_rate) as usize, 1));
+
+        let travel_at = |(data, rate): (&[f32], f32), time: f32| {
+            data.get((time * rate) as usize).copied().unwrap_or(0.0)
+        };
+        let in_air = |i: usize| {
+            let time = i as f32 / imu_rate;
+            smoothed[i] < self.free_fall_g
+                && travel_at(rear, time) < self.max_air_travel
+                && travel_at(front, time) < self.max_air_travel
+        };
+
+        let mut jumps = Vec::new();
+        let mut i = 0;
+        while i < smoothed.len() {
+            if !in_air(i) {
+                i += 1;
+                continue;
+            }
+
+            let take_off_index = i;
+            while i < smoothed.len() && in_air(i) {
+                i += 1;
+            }
+            let (take_off, landing) = (take_off_index as f32 / imu_rate, i as f32 / imu_rate);
+            if landing - take_off < self.min_air_time {
+                continue;
+            }
+
+            let window_end = landing + self.landing_window;
+            // the window end can round to before the landing sample, most of all with no landing window
+            let peak_end = usize::max(usize::min((window_end * imu_rate) as usize, magnitudes.len()), i);
+            let landing_peak_g = magnitudes[i..peak_end]
+                .iter()
+                .fold(0.0, |max: f32, m| max.max(*m));
+            let deepest = |(data, rate): (&[f32], f32)| {
+                let end = usize::min((window_end * rate) as usize, data.len());
+                let start = usize::min((landing * rate) as usize, end);
+                data[start..end].iter().fold(0.0, |max: f32, t| max.max(*t))
+            };
+            let (rear_landing_travel, front_landing_travel) = (deepest(rear), deepest(front));
+
+            if f32::max(rear_landing_travel, front_landing_travel) < self.min_landing_travel {
+                continue;
+            }
+
+            jumps.push(Jump {
+                take_off,
+                landing,
+                air_time: landing - take_off,
+                landing_peak_g,
+                rear_landing_travel,
+                front_landing_travel,
+            });
+        }
+
+        jumps
+    }
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+use crate::calibration::{interpolate, segment};
+use crate::config_info::SuspensionRemapInfo;
+
+/// Table of shock stroke against rear wheel travel for a linkage, interpolated linearly between points
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct LeverageCurve {
+    /// (shock stroke in mm, wheel travel in mm) pairs, kept sorted by shock stroke
+    pub points: Vec<(f32, f32)>,
+}
+
+impl LeverageCurve {
+    /// A curve needs at least two points to interpolate between
+    pub fn is_valid(&self) -> bool {
+        self.points.len() >= 2
+    }
+
+    pub fn sort(&mut self) {
+        self.points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+
+    /// Rear wheel travel in mm for a shock stroke in mm, extrapolated from the end segments outside the table
+    pub fn wheel_travel(&self, shock_stroke: f32) -> f32 {
+        if !self.is_valid() {
+            return shock_stroke;
+        }
+
+        interpolate(&self.points, shock_stroke)
+    }
+
+    /// Leverage ratio (wheel travel / shock stroke) of the segment `shock_stroke` falls in
+    pub fn leverage_ratio(&self, shock_stroke: f32) -> f32 {
+        let i = match segment(&self.points, shock_stroke) {
+            Some(i) => i,
+            None => return 1.0,
+        };
+        let ((s0, w0), (s1, w1)) = (self.points[i], self.points[i + 1]);
+        if s1 - s0 == 0.0 {
+            return 1.0;
+        }
+
+        (w1 - w0) / (s1 - s0)
+    }
+}
+
+/// Wheel travel in mm for a mapped travel in %, going through the leverage curve when there is one
+///
+/// # Arguments
+///
+/// `remap_info`: The remap info of the channel, which must have its stroke set
+/// `curve`: The leverage curve of the bike, None for a fork or a bike without a curve
+/// `val`: The mapped travel in %
+pub fn remap_wheel_travel_mm(remap_info: &SuspensionRemapInfo, curve: Option<&LeverageCurve>, val: f32) -> f32 {
+    match curve {
+        Some(c) if c.is_valid() => c.wheel_travel(remap_info.remap_stroke_mm(val)),
+        _ => remap_info.remap_wheel_travel_mm(val),
+    }
+}
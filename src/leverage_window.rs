@@ -0,0 +1,113 @@
+use egui::{Color32, Context, Id};
+use egui_plot::{Line, Plot, PlotPoints, Points};
+
+use crate::config_info::BikeConfig;
+
+/// Window for viewing and editing the leverage curve of the selected bike
+pub struct LeverageWindow {
+    pub open: bool,
+}
+
+impl LeverageWindow {
+    pub fn new() -> LeverageWindow {
+        LeverageWindow { open: false }
+    }
+
+    /// Draw the window
+    ///
+    /// # Arguments
+    ///
+    /// `ctx`: The eGui context
+    /// `bike`: The selected bike, None if no bike is selected
+    ///
+    /// # Returns
+    ///
+    /// true if the curve was changed and the data needs to be recalculated
+    pub fn update(&mut self, ctx: &Context, bike: Option<&mut BikeConfig>) -> bool {
+        let mut open = self.open;
+        let mut changed = false;
+
+        egui::Window::new("Leverage curve").open(&mut open).show(ctx, |ui| {
+            let bike = match bike {
+                Some(b) => b,
+                None => {
+                    ui.label("Select a bike to edit its leverage curve");
+                    return;
+                }
+            };
+            let curve = &mut bike.leverage_curve;
+
+            ui.label("shock stroke (mm) / rear wheel travel (mm)");
+
+            let mut remove = None;
+            // the points are sorted once an edit is finished rather than while dragging, so a row does not jump
+            // away from under the pointer
+            let mut edited = false;
+            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                for (i, (shock_stroke, wheel_travel)) in curve.points.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        let stroke_response = ui.add(egui::DragValue::new(shock_stroke).clamp_range(0.0..=200.0).speed(0.1));
+                        ui.add(egui::DragValue::new(wheel_travel).clamp_range(0.0..=400.0).speed(0.1));
+                        edited |= stroke_response.drag_released() || stroke_response.lost_focus();
+                        if ui.button("remove").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+            });
+            if let Some(i) = remove {
+                curve.points.remove(i);
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Add point").clicked() {
+                    let last = curve.points.last().copied().unwrap_or((0.0, 0.0));
+                    curve.points.push((last.0 + 5.0, last.1 + 15.0));
+                    edited = true;
+                }
+                if ui.button("Apply").clicked() {
+                    changed = true;
+                }
+            });
+            if edited || changed {
+                curve.sort();
+            }
+
+            if !curve.is_valid() {
+                ui.label("The curve needs at least two points, the rear is converted linearly until then");
+                return;
+            }
+
+            let travel_points: Vec<[f64; 2]> = curve.points.iter().map(|(s, w)| [*s as f64, *w as f64]).collect();
+            let ratio_points: Vec<[f64; 2]> = curve.points.windows(2).flat_map(|pair| {
+                let ratio = curve.leverage_ratio(pair[0].0) as f64;
+                [[pair[0].1 as f64, ratio], [pair[1].1 as f64, ratio]]
+            }).collect();
+
+            ui.horizontal(|ui| {
+                Plot::new("leverage_travel")
+                    .id(Id::new("leverage_travel"))
+                    .width(250.0)
+                    .height(250.0)
+                    .x_axis_label("shock stroke (mm)")
+                    .y_axis_label("wheel travel (mm)")
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(Line::new(PlotPoints::new(travel_points.clone())).color(Color32::RED));
+                        plot_ui.points(Points::new(PlotPoints::new(travel_points)).radius(3.0).color(Color32::RED));
+                    });
+                Plot::new("leverage_ratio")
+                    .id(Id::new("leverage_ratio"))
+                    .width(250.0)
+                    .height(250.0)
+                    .x_axis_label("wheel travel (mm)")
+                    .y_axis_label("leverage ratio")
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(Line::new(PlotPoints::new(ratio_points)).color(Color32::LIGHT_BLUE));
+                    });
+            });
+        });
+
+        self.open = open;
+        changed
+    }
+}
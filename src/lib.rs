@@ -8,6 +8,34 @@ mod config_window;
 mod graph;
 mod view;
 mod initial_config;
+mod filter;
+mod stroke;
+mod stroke_table;
+mod travel_events;
+mod leverage;
+mod leverage_window;
+mod calibration;
+mod calibration_window;
+mod calibration_wizard;
+mod activity;
+mod sag;
+mod sections;
+mod sections_window;
+mod jumps;
+mod jump_table;
+mod braking;
+mod braking_table;
+mod balance;
+mod regression;
+mod damper;
+mod spring;
+mod packdown;
+mod orientation;
+mod distance;
+mod wheel_speed;
+mod spectrum;
+mod travel_stats;
+mod travel_stats_window;
 
 
 pub use config_info::ConfigInfo;
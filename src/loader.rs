@@ -4,21 +4,70 @@ use std::io;
 use std::io::prelude::*;
 use std::ops::{Bound, RangeBounds};
 
+use crate::wheel_speed::PulseKind;
+
 pub struct RawPotData {
     pub remap_ref: String,
     pub offset: u32,
     pub polling_rate: u32,
+    /// The samples at `polling_rate`, a slower channel only takes the rows its samples fall on
     pub data: Vec<u32>,
 }
 
+/// The IMU columns at the start of each row of a run file
+pub struct ImuData {
+    /// Rows are logged at the rate of the fastest channel
+    pub polling_rate: u32,
+    /// Acceleration along x, y, z in m/s²
+    pub accel: Vec<[f32; 3]>,
+    /// Angular rate about x, y, z in rad/s
+    pub gyro: Vec<[f32; 3]>,
+}
+
+impl ImuData {
+    /// Names of the single axis channels `channel` gives
+    pub const CHANNEL_NAMES: [&'static str; 6] = ["accel x", "accel y", "accel z", "gyro x", "gyro y", "gyro z"];
+
+    pub fn new() -> ImuData {
+        ImuData {
+            polling_rate: 0,
+            accel: Vec::new(),
+            gyro: Vec::new(),
+        }
+    }
+
+    /// One axis of the accelerometer or gyro, by one of the names in `CHANNEL_NAMES`
+    pub fn channel(&self, name: &str) -> Option<Vec<f32>> {
+        let axis = ImuData::CHANNEL_NAMES.iter().position(|n| *n == name)?;
+        let samples = if axis < 3 { &self.accel } else { &self.gyro };
+        Some(samples.iter().map(|s| s[axis % 3]).collect())
+    }
+
+    /// Older loggers wrote the IMU columns as zeros, so the IMU only counts as present if it ever reads non zero
+    pub fn is_present(&self) -> bool {
+        self.polling_rate > 0 && self.accel.iter().any(|a| a.iter().any(|v| *v != 0.0))
+    }
+}
+
+/// A wheel speed sensor channel, its samples are kept with the other channels in `raw_pot_datas`
+pub struct WheelSpeedChannel {
+    pub tag: String,
+    pub kind: PulseKind,
+}
+
 pub struct Loader {
     pub raw_pot_datas: HashMap<String, RawPotData>,
+    pub imu: ImuData,
+    /// The first channel the header declares as pulse counts or pulse periods, None if the run has no wheel speed
+    pub wheel_speed: Option<WheelSpeedChannel>,
 }
 
 impl Loader {
     pub fn new() -> Loader {
         Loader {
             raw_pot_datas: HashMap::new(),
+            imu: ImuData::new(),
+            wheel_speed: None,
         }
     }
 
@@ -26,12 +75,20 @@ impl Loader {
         self.raw_pot_datas.get(&key).expect("Error: Data not found")
     }
 
+    /// The samples of the wheel speed channel and how it records the pulses, if the run has one
+    pub fn get_wheel_speed_data(&self) -> Option<(&RawPotData, PulseKind)> {
+        let channel = self.wheel_speed.as_ref()?;
+        self.raw_pot_datas.get(&channel.tag).map(|pot| (pot, channel.kind))
+    }
+
     /// takes a String path [path] and returns instace of bufReader
     /// - [x] Load all data
     /// - [ ]  Save time data to allow easier referencing
     /// - [ ]  Implement rolling loading 
     pub fn load(&mut self, path: String) {
         self.raw_pot_datas.clear();
+        self.imu = ImuData::new();
+        self.wheel_speed = None;
 
         let file = File::open(path.trim()).unwrap();
         let mut lines = io::BufReader::new(&file).lines();
@@ -47,24 +104,39 @@ impl Loader {
             let remap_ref = tag_rate_iter.next().expect("Error: Invalid metadata").to_string();
             let offset = str_offset.parse::<u32>().unwrap();
             println!("{:?}",&rate);
+            // wheel speed channels declare how they record the pulses where the others name their remap reference
+            if let Some(kind) = PulseKind::from_header(&remap_ref) {
+                if self.wheel_speed.is_none() {
+                    self.wheel_speed = Some(WheelSpeedChannel { tag: tag.to_string(), kind });
+                }
+            }
             pot_data_is.push(tag.to_string());
             self.raw_pot_datas.insert(tag.to_owned(), RawPotData {
                 remap_ref, offset, polling_rate: rate, data: Vec::new()
             });
         }
-        
-        
+        self.imu.polling_rate = self.raw_pot_datas.values().map(|p| p.polling_rate).max().unwrap_or(0);
 
-        
-        //does not filter out 
-        for line in lines {
+        let row_rate = self.imu.polling_rate as u64;
+        for (row, line) in lines.enumerate() {
             
             let lineHolder = line.unwrap();
             let vals = lineHolder.split(',');
+
+            let imu_vals: Vec<f32> = vals.clone().take(6).filter_map(|v| v.parse::<f32>().ok()).collect();
+            if imu_vals.len() == 6 {
+                self.imu.accel.push([imu_vals[0], imu_vals[1], imu_vals[2]]);
+                self.imu.gyro.push([imu_vals[3], imu_vals[4], imu_vals[5]]);
+            }
             
             for (i, val) in vals.enumerate() {
                 if i >= 6 {
-                    self.raw_pot_datas.get_mut(&pot_data_is[i - 6]).unwrap().data.push(truncate_val(val));
+                    let pot_data = self.raw_pot_datas.get_mut(&pot_data_is[i - 6]).unwrap();
+                    // every channel has a column in every row, the ones slower than the row rate repeat their
+                    // samples so only the rows a new sample is due on are kept
+                    if pot_data.data.len() as u64 * row_rate <= row as u64 * pot_data.polling_rate as u64 {
+                        pot_data.data.push(truncate_val(val));
+                    }
                 }
             }
         }
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+use crate::jumps::GRAVITY;
+use crate::loader::ImuData;
+use crate::sag::STATIC_SAG_TIME;
+
+/// Pitch and roll of the bike over a run, from the IMU
+///
+/// The IMU axes are taken as x forward, y left and z up. Pitch is positive nose up and roll is positive leaning right.
+pub struct Orientation {
+    /// Pitch in degrees at each IMU sample
+    pub pitch: Vec<f32>,
+    /// Roll in degrees at each IMU sample
+    pub roll: Vec<f32>,
+    /// Acceleration along the bike with gravity removed in m/s² at each IMU sample
+    pub forward_accel: Vec<f32>,
+}
+
+/// Complementary filter fusing the gyro and accelerometer into pitch and roll
+///
+/// The gyro is integrated for the short term, which is smooth but drifts, and is pulled towards the angle of gravity
+/// from the accelerometer over the time constant, which is noisy but does not drift.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct OrientationFilter {
+    /// Time in seconds over which the accelerometer corrects the gyro, longer trusts the gyro more
+    pub time_constant: f32,
+    /// How far in g the acceleration may be from 1 g for it to be trusted as gravity, landings and hard corners
+    /// are left to the gyro
+    pub accel_tolerance: f32,
+    /// Take the attitude at the start of the run, where the bike stands still for the static sag, as level so a
+    /// tilted logger reads zero
+    pub level_at_start: bool,
+}
+
+impl Default for OrientationFilter {
+    fn default() -> OrientationFilter {
+        OrientationFilter {
+            time_constant: 0.5,
+            accel_tolerance: 0.2,
+            level_at_start: true,
+        }
+    }
+}
+
+const IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Pitch and roll in radians of the gravity vector measured by the accelerometer
+fn accel_angles(accel: &[f32; 3]) -> (f32, f32) {
+    let [x, y, z] = *accel;
+    (x.atan2((y * y + z * z).sqrt()), y.atan2(z))
+}
+
+/// Rotation matrix turning the direction `from` onto straight up, about the axis perpendicular to both
+fn levelling_rotation(from: [f32; 3]) -> [[f32; 3]; 3] {
+    let length = (from[0] * from[0] + from[1] * from[1] + from[2] * from[2]).sqrt();
+    if length == 0.0 {
+        return IDENTITY;
+    }
+    let [x, y, z] = [from[0] / length, from[1] / length, from[2] / length];
+    // upside down has no single axis to turn about, turn about x
+    if z <= -1.0 + f32::EPSILON {
+        return [[1.0, 0.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, -1.0]];
+    }
+
+    // Rodrigues' formula with the axis from x cross up, (y, -x, 0), and cos of the angle z
+    let k = 1.0 / (1.0 + z);
+    [
+        [1.0 - x * x * k, -x * y * k, -x],
+        [-x * y * k, 1.0 - y * y * k, -y],
+        [x, y, z],
+    ]
+}
+
+fn rotate(m: &[[f32; 3]; 3], v: &[f32; 3]) -> [f32; 3] {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+impl OrientationFilter {
+    /// Run the filter over the IMU samples of a run
+    ///
+    /// # Returns
+    ///
+    /// The pitch and roll, empty if the run has no IMU data
+    pub fn apply(&self, imu: &ImuData) -> Orientation {
+        let mut orientation = Orientation {
+            pitch: Vec::with_capacity(imu.accel.len()),
+            roll: Vec::with_capacity(imu.accel.len()),
+            forward_accel: Vec::with_capacity(imu.accel.len()),
+        };
+        let first = match imu.accel.first() {
+            Some(a) if imu.polling_rate > 0 => a,
+            _ => return orientation,
+        };
+
+        let dt = 1.0 / imu.polling_rate as f32;
+        let alpha = self.time_constant / (self.time_constant + dt);
+
+        let mut levelling = IDENTITY;
+        if self.level_at_start {
+            let static_len = usize::clamp((STATIC_SAG_TIME * imu.polling_rate as f32) as usize, 1, imu.accel.len());
+            let mut gravity = [0.0; 3];
+            for a in &imu.accel[..static_len] {
+                for axis in 0..3 {
+                    gravity[axis] += a[axis];
+                }
+            }
+            levelling = levelling_rotation(gravity);
+        }
+        let (mut pitch, mut roll) = accel_angles(&rotate(&levelling, first));
+
+        for (accel, gyro) in imu.accel.iter().zip(&imu.gyro) {
+            let (accel, gyro) = (&rotate(&levelling, accel), rotate(&levelling, gyro));
+            // body rates with y right and z down so the usual Euler angle kinematics apply
+            let (p, q, r) = (gyro[0], -gyro[1], -gyro[2]);
+            let roll_rate = p + (roll.sin() * q + roll.cos() * r) * pitch.tan();
+            let pitch_rate = roll.cos() * q - roll.sin() * r;
+            pitch += pitch_rate * dt;
+            roll += roll_rate * dt;
+
+            let g = (accel[0] * accel[0] + accel[1] * accel[1] + accel[2] * accel[2]).sqrt() / GRAVITY;
+            if (g - 1.0).abs() <= self.accel_tolerance {
+                let (accel_pitch, accel_roll) = accel_angles(accel);
+                pitch = alpha * pitch + (1.0 - alpha) * accel_pitch;
+                roll = alpha * roll + (1.0 - alpha) * accel_roll;
+            }
+
+            orientation.pitch.push(pitch.to_degrees());
+            orientation.roll.push(roll.to_degrees());
+            // nose up the accelerometer sees part of gravity along the bike
+            orientation.forward_accel.push(accel[0] - GRAVITY * pitch.sin());
+        }
+
+        orientation
+    }
+}
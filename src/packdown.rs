@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+
+use crate::stroke::{Stroke, StrokeDirection};
+
+/// A run of hits where the suspension did not recover between compressions, so it sat deeper with each one
+#[derive(Clone, Copy, Debug)]
+pub struct PackDown {
+    /// Time the first stroke of the run starts at in seconds
+    pub start: f32,
+    /// Time the last stroke of the run ends at in seconds
+    pub end: f32,
+    /// Number of compressions in the run
+    pub hits: usize,
+    /// How much deeper the middle of the strokes sat at the end of the run than at the start, in % travel
+    pub drift: f32,
+}
+
+/// Finds pack down in the strokes of one end
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct PackDownDetector {
+    /// Fewest compressions in a row that count as packing down
+    pub min_hits: usize,
+    /// Smallest drift deeper over the run in % travel
+    pub min_drift: f32,
+    /// How far the middle of a stroke may come back up before the run is over, in % travel
+    pub tolerance: f32,
+    /// Longest time between strokes in seconds before the run is over, pack down needs hits in quick succession
+    pub max_gap: f32,
+}
+
+impl Default for PackDownDetector {
+    fn default() -> PackDownDetector {
+        PackDownDetector {
+            min_hits: 4,
+            min_drift: 10.0,
+            tolerance: 2.0,
+            max_gap: 0.5,
+        }
+    }
+}
+
+impl PackDownDetector {
+    /// Find the runs of strokes whose middle ratchets deeper
+    ///
+    /// # Arguments
+    ///
+    /// `travel`: The travel in %
+    /// `strokes`: The strokes in time order, with indices into `travel`
+    pub fn detect(&self, travel: &[f32], strokes: &[Stroke]) -> Vec<PackDown> {
+        let mid = |stroke: &Stroke| (travel[stroke.start_index] + travel[stroke.end_index]) / 2.0;
+        let mut pack_downs = Vec::new();
+
+        // the run is strokes[run_start..i], it carries on while each stroke sits no higher than the deepest so far
+        let mut run_start = 0;
+        let mut deepest = f32::MIN;
+        for i in 0..=strokes.len() {
+            let carries_on = i < strokes.len() && i > run_start && {
+                let gap = strokes[i].start - strokes[i - 1].end;
+                gap <= self.max_gap && mid(&strokes[i]) >= deepest - self.tolerance
+            };
+            if carries_on {
+                deepest = f32::max(deepest, mid(&strokes[i]));
+                continue;
+            }
+
+            if i > run_start {
+                let run = &strokes[run_start..i];
+                let hits = run.iter().filter(|s| s.direction == StrokeDirection::Compression).count();
+                let drift = mid(&run[run.len() - 1]) - mid(&run[0]);
+                if hits >= self.min_hits && drift >= self.min_drift {
+                    pack_downs.push(PackDown { start: run[0].start, end: run[run.len() - 1].end, hits, drift });
+                }
+            }
+            if i < strokes.len() {
+                run_start = i;
+                deepest = mid(&strokes[i]);
+            }
+        }
+
+        pack_downs
+    }
+}
+
+/// Pack down score of a run, the depth gained while packing down per minute analysed
+///
+/// # Arguments
+///
+/// `pack_downs`: The pack downs found over the analysis range
+/// `duration`: Length of the analysis range in seconds
+///
+/// # Returns
+///
+/// The score in % travel per minute, 0 when the suspension always recovered
+pub fn score(pack_downs: &[PackDown], duration: f32) -> f32 {
+    if duration <= 0.0 {
+        return 0.0;
+    }
+    pack_downs.iter().map(|p| p.drift).sum::<f32>() * 60.0 / duration
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Strokes 0.1 s long between turning points at consecutive samples of `travel`
+    fn strokes(travel: &[f32]) -> Vec<Stroke> {
+        travel.windows(2).enumerate().map(|(i, pair)| Stroke {
+            start_index: i,
+            end_index: i + 1,
+            start: i as f32 * 0.1,
+            end: (i + 1) as f32 * 0.1,
+            peak: f32::max(pair[0], pair[1]),
+            amplitude: (pair[1] - pair[0]).abs(),
+            duration: 0.1,
+            peak_velocity: (pair[1] - pair[0]).abs() / 0.1,
+            direction: if pair[1] > pair[0] { StrokeDirection::Compression } else { StrokeDirection::Rebound },
+        }).collect()
+    }
+
+    /// Compresses 30 and rebounds 20, so the middle of each stroke sits 5 deeper than the one before
+    const RATCHET: [f32; 10] = [10.0, 40.0, 20.0, 50.0, 30.0, 60.0, 40.0, 70.0, 50.0, 80.0];
+
+    #[test]
+    fn ratcheting_is_found() {
+        let pack_downs = PackDownDetector::default().detect(&RATCHET, &strokes(&RATCHET));
+
+        assert_eq!(pack_downs.len(), 1);
+        let pack_down = pack_downs[0];
+        assert_eq!(pack_down.hits, 5);
+        assert!((pack_down.drift - 40.0).abs() < 1e-4);
+        assert_eq!(pack_down.start, 0.0);
+        // the run carries on to the last stroke
+        assert!((pack_down.end - 0.9).abs() < 1e-4);
+        assert!((score(&pack_downs, 120.0) - 20.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn recovery_past_the_tolerance_ends_the_run() {
+        let mut travel = RATCHET.to_vec();
+        travel.extend([10.0, 40.0]);
+        let detector = PackDownDetector { min_hits: 2, min_drift: 5.0, ..PackDownDetector::default() };
+        let pack_downs = detector.detect(&travel, &strokes(&travel));
+
+        // the rebound to 10 sits far above the deepest stroke so starts a new run, which has too few hits
+        assert_eq!(pack_downs.len(), 1);
+        assert_eq!(pack_downs[0].hits, 5);
+        assert!((pack_downs[0].end - 0.9).abs() < 1e-4);
+    }
+
+    #[test]
+    fn long_gap_splits_the_run() {
+        let mut ratchet = strokes(&RATCHET);
+        for stroke in &mut ratchet[5..] {
+            stroke.start += 1.0;
+            stroke.end += 1.0;
+        }
+        let detector = PackDownDetector { min_hits: 2, min_drift: 5.0, ..PackDownDetector::default() };
+        let pack_downs = detector.detect(&RATCHET, &ratchet);
+
+        assert_eq!(pack_downs.len(), 2);
+        assert_eq!((pack_downs[0].hits, pack_downs[1].hits), (3, 2));
+        assert!((pack_downs[0].drift - 20.0).abs() < 1e-4);
+        assert!((pack_downs[1].drift - 15.0).abs() < 1e-4);
+        assert!((pack_downs[0].end - 0.5).abs() < 1e-4);
+        assert!((pack_downs[1].start - 1.5).abs() < 1e-4);
+
+        // with the default detector neither half has enough hits
+        assert!(PackDownDetector::default().detect(&RATCHET, &ratchet).is_empty());
+    }
+
+    #[test]
+    fn recovering_suspension_is_not_packing_down() {
+        let travel: Vec<f32> = (0..20).map(|i| if i % 2 == 0 { 10.0 } else { 40.0 }).collect();
+
+        assert!(PackDownDetector::default().detect(&travel, &strokes(&travel)).is_empty());
+        assert_eq!(score(&[], 60.0), 0.0);
+    }
+}
@@ -0,0 +1,147 @@
+/// Fewest points a line is fitted to
+pub const MIN_POINTS: usize = 3;
+
+/// Two sided 95 % quantile of the normal distribution, used for the width of the confidence band
+const CONFIDENCE_Z: f32 = 1.96;
+
+/// Scales the median absolute deviation to a standard deviation for normally distributed residuals
+const MAD_TO_STD: f32 = 1.4826;
+
+/// A straight line fitted to (x, y) points
+#[derive(Clone, Copy, Debug)]
+pub struct LineFit {
+    pub slope: f32,
+    pub intercept: f32,
+    /// Share of the variance of y explained by the line, 1.0 for a perfect fit, below 0.0 when outliers dominate
+    pub r_squared: f32,
+    /// Robust estimate of the standard deviation of the residuals
+    pub residual_std: f32,
+    /// Number of points fitted
+    pub count: usize,
+    x_mean: f32,
+    /// Sum of squared deviations of x from its mean
+    x_spread: f32,
+}
+
+impl LineFit {
+    /// The fitted y at `x`
+    pub fn predict(&self, x: f32) -> f32 {
+        self.intercept + self.slope * x
+    }
+
+    /// Half width of the 95 % confidence band of the fitted line at `x`, widening away from the centre of the data
+    pub fn confidence(&self, x: f32) -> f32 {
+        CONFIDENCE_Z * self.residual_std * (1.0 / self.count as f32 + (x - self.x_mean).powi(2) / self.x_spread).sqrt()
+    }
+}
+
+fn median(values: &mut [f32]) -> f32 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Theil-Sen line fit, the median of the slopes between every pair of points
+///
+/// A few strokes with odd velocities, e.g. from a sensor glitch, barely move the fit unlike least squares.
+///
+/// # Returns
+///
+/// The fit, None if there are fewer than `MIN_POINTS` points or all the x values are the same
+pub fn theil_sen(points: &[(f32, f32)]) -> Option<LineFit> {
+    if points.len() < MIN_POINTS {
+        return None;
+    }
+
+    let mut slopes = Vec::with_capacity(points.len() * (points.len() - 1) / 2);
+    for (i, (x1, y1)) in points.iter().enumerate() {
+        for (x2, y2) in &points[i + 1..] {
+            if x2 != x1 {
+                slopes.push((y2 - y1) / (x2 - x1));
+            }
+        }
+    }
+    if slopes.is_empty() {
+        return None;
+    }
+    let slope = median(&mut slopes);
+    let intercept = median(&mut points.iter().map(|(x, y)| y - slope * x).collect::<Vec<f32>>());
+
+    let count = points.len();
+    let x_mean = points.iter().map(|(x, _)| x).sum::<f32>() / count as f32;
+    let y_mean = points.iter().map(|(_, y)| y).sum::<f32>() / count as f32;
+    let x_spread = points.iter().map(|(x, _)| (x - x_mean).powi(2)).sum();
+    let residuals: Vec<f32> = points.iter().map(|(x, y)| y - (intercept + slope * x)).collect();
+
+    let residual_sq: f32 = residuals.iter().map(|r| r * r).sum();
+    let total_sq: f32 = points.iter().map(|(_, y)| (y - y_mean).powi(2)).sum();
+    let r_squared = if total_sq > 0.0 { 1.0 - residual_sq / total_sq } else { 1.0 };
+
+    let residual_std = MAD_TO_STD * median(&mut residuals.iter().map(|r| r.abs()).collect::<Vec<f32>>());
+
+    Some(LineFit { slope, intercept, r_squared, residual_std, count, x_mean, x_spread })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(slope: f32, intercept: f32, count: usize) -> Vec<(f32, f32)> {
+        (0..count).map(|i| (i as f32, intercept + slope * i as f32)).collect()
+    }
+
+    #[test]
+    fn exact_line_is_recovered() {
+        let fit = theil_sen(&line(2.5, -4.0, 10)).unwrap();
+
+        assert!((fit.slope - 2.5).abs() < 1e-5);
+        assert!((fit.intercept + 4.0).abs() < 1e-5);
+        assert!((fit.r_squared - 1.0).abs() < 1e-5);
+        assert_eq!(fit.residual_std, 0.0);
+        assert_eq!(fit.count, 10);
+    }
+
+    #[test]
+    fn outliers_barely_move_the_fit() {
+        let mut points = line(2.5, -4.0, 20);
+        points[5].1 += 500.0;
+        points[13].1 -= 300.0;
+        let fit = theil_sen(&points).unwrap();
+
+        assert!((fit.slope - 2.5).abs() < 0.1);
+        assert!((fit.intercept + 4.0).abs() < 1.0);
+        // the outliers are left out of the spread, which least squares would be pulled far off by
+        assert!(fit.residual_std < 1.0);
+        assert!(fit.r_squared < 0.5);
+    }
+
+    #[test]
+    fn too_few_points_give_no_fit() {
+        for count in 0..MIN_POINTS {
+            assert!(theil_sen(&line(1.0, 0.0, count)).is_none());
+        }
+        assert!(theil_sen(&line(1.0, 0.0, MIN_POINTS)).is_some());
+    }
+
+    #[test]
+    fn equal_x_gives_no_fit() {
+        assert!(theil_sen(&[(3.0, 1.0), (3.0, 2.0), (3.0, 5.0), (3.0, -1.0)]).is_none());
+    }
+
+    #[test]
+    fn confidence_widens_away_from_the_centre() {
+        let points: Vec<(f32, f32)> = line(1.0, 0.0, 21).into_iter()
+            .enumerate()
+            .map(|(i, (x, y))| (x, y + 0.5 * (i as f32 * 1.7).sin()))
+            .collect();
+        let fit = theil_sen(&points).unwrap();
+
+        assert!(fit.residual_std > 0.0);
+        assert!(fit.confidence(10.0) < fit.confidence(0.0));
+        assert!((fit.confidence(0.0) - fit.confidence(20.0)).abs() < 1e-5);
+    }
+}
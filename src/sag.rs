@@ -0,0 +1,87 @@
+use crate::data::sample_range;
+
+/// Time in seconds before the riding starts which is taken as the rider sat still on the bike for static sag
+pub const STATIC_SAG_TIME: f32 = 2.0;
+
+/// Static sag measured by the calibration wizard as (% travel, wheel travel in mm if known)
+pub type CalibratedSag = (f32, Option<f32>);
+
+/// Static and dynamic sag of one end of the bike
+#[derive(Clone, Copy, Debug)]
+pub struct EndSag {
+    /// Median travel in % while sat still just before the riding starts, or the calibrated static sag if the run
+    /// starts with riding, None if neither is known
+    pub static_percent: Option<f32>,
+    /// Mean travel in % while riding
+    pub dynamic_percent: f32,
+    /// Static sag as wheel travel in mm, None if the remap info has no wheel travel
+    pub static_mm: Option<f32>,
+    /// Dynamic sag as wheel travel in mm, None if the remap info has no wheel travel
+    pub dynamic_mm: Option<f32>,
+}
+
+impl EndSag {
+    /// Measure the sag of one end
+    ///
+    /// # Arguments
+    ///
+    /// `percent`: The mapped travel in %
+    /// `mm`: The wheel travel in mm, sampled the same as `percent`, None if it is not known
+    /// `polling_rate`: The polling rate of the channel in Hz
+    /// `range`: (start, end) in seconds of the riding portion
+    pub fn measure(percent: &[f32], mm: Option<&[f32]>, polling_rate: f32, range: (f32, f32)) -> EndSag {
+        let riding = sample_range(range, polling_rate, percent.len());
+        let idle = sample_range((range.0 - STATIC_SAG_TIME, range.0), polling_rate, percent.len());
+        // the idle period has to be the full length, a shorter one is just the run starting mid ride
+        let has_static = (idle.end - idle.start) as f32 >= STATIC_SAG_TIME * polling_rate;
+
+        EndSag {
+            static_percent: Some(median(&percent[idle.clone()])).filter(|_| has_static),
+            dynamic_percent: mean(&percent[riding.clone()]),
+            static_mm: mm.map(|d| median(&d[idle])).filter(|_| has_static),
+            dynamic_mm: mm.map(|d| mean(&d[riding])),
+        }
+    }
+
+    /// Fill in the static sag from a calibration if the run had no time sat still before the riding
+    ///
+    /// # Arguments
+    ///
+    /// `calibrated`: The static sag measured by the calibration wizard, None if the wizard did not measure it
+    pub fn or_calibrated(self, calibrated: Option<CalibratedSag>) -> EndSag {
+        match (self.static_percent, calibrated) {
+            (None, Some((percent, mm))) => EndSag { static_percent: Some(percent), static_mm: mm, ..self },
+            _ => self,
+        }
+    }
+}
+
+/// Sag of both ends over the riding portion of a run
+#[derive(Clone, Copy, Debug)]
+pub struct SagMetrics {
+    pub front: EndSag,
+    pub rear: EndSag,
+}
+
+impl SagMetrics {
+    /// Front dynamic sag minus rear dynamic sag in % travel, positive when the front sits deeper in its travel
+    pub fn balance(&self) -> f32 {
+        self.front.dynamic_percent - self.rear.dynamic_percent
+    }
+}
+
+fn mean(data: &[f32]) -> f32 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    data.iter().sum::<f32>() / data.len() as f32
+}
+
+fn median(data: &[f32]) -> f32 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = data.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted[sorted.len() / 2]
+}
@@ -0,0 +1,67 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// A named time range of a run, e.g. a rock garden
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Section {
+    pub name: String,
+    /// Time the section starts at in seconds
+    pub start: f32,
+    /// Time the section ends at in seconds
+    pub end: f32,
+}
+
+/// A named point in time of a run, e.g. a drop
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Marker {
+    pub name: String,
+    /// Time of the marker in seconds
+    pub time: f32,
+}
+
+/// The sections and markers of a run, kept in a sidecar file next to the run file
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct RunAnnotations {
+    pub sections: Vec<Section>,
+    pub markers: Vec<Marker>,
+}
+
+impl RunAnnotations {
+    /// The sidecar file for a run, the run file with its extension replaced, e.g. RUN4.TXT -> RUN4.sections.json
+    pub fn sidecar_path(run_path: &str) -> PathBuf {
+        Path::new(run_path.trim()).with_extension("sections.json")
+    }
+
+    /// Load the annotations of a run, a run without a sidecar file has no annotations
+    ///
+    /// # Returns
+    ///
+    /// The annotations, or a message saying why the sidecar file could not be read
+    pub fn load(run_path: &str) -> Result<RunAnnotations, String> {
+        let file = match File::open(RunAnnotations::sidecar_path(run_path)) {
+            Ok(f) => f,
+            Err(_) => return Ok(RunAnnotations::default()),
+        };
+
+        serde_json::from_reader(BufReader::new(file)).map_err(|e| format!("Error reading sections file: {}", e))
+    }
+
+    /// Save the annotations of a run to its sidecar file
+    ///
+    /// # Returns
+    ///
+    /// A message saying why the file could not be written on failure
+    pub fn save(&self, run_path: &str) -> Result<(), String> {
+        let file = File::create(RunAnnotations::sidecar_path(run_path)).map_err(|e| format!("Error creating sections file: {}", e))?;
+        let buf_writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(buf_writer, &self).map_err(|e| format!("Error saving to sections file: {}", e))
+    }
+
+    /// The first section called `name`, sections are matched by name to compare the same stretch across runs
+    pub fn find_section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+}
@@ -0,0 +1,153 @@
+use egui::Context;
+
+use crate::graph::suspension_graph::SuspensionGraph;
+use crate::sections::{Marker, RunAnnotations, Section};
+
+/// Time either side of a section or marker shown when zooming to it
+const ZOOM_PADDING: f64 = 1.0;
+
+/// Window for naming sections and markers of the loaded run and picking a section to analyse
+pub struct SectionsWindow {
+    pub open: bool,
+    /// Whether the annotations have been edited since they were loaded or saved
+    unsaved: bool,
+    /// Why the sidecar file of the run could not be loaded or saved, None if it was fine
+    error: Option<String>,
+}
+
+impl SectionsWindow {
+    pub fn new() -> SectionsWindow {
+        SectionsWindow {
+            open: false,
+            unsaved: false,
+            error: None,
+        }
+    }
+
+    /// Draw the window
+    ///
+    /// # Arguments
+    ///
+    /// `ctx`: The eGui context
+    /// `annotations`: The sections and markers of the loaded run
+    /// `run_path`: The path of the loaded run, the annotations are saved next to it
+    /// `analysed_section`: The name of the section being analysed, None to analyse the selected range
+    /// `analysis_range`: (start, end) in seconds of the range currently analysed, None if nothing is loaded
+    ///
+    /// # Returns
+    ///
+    /// true if the annotations were saved or a section was picked and the data needs to be recalculated
+    pub fn update(&mut self, ctx: &Context, annotations: &mut RunAnnotations, run_path: &str, analysed_section: &mut Option<String>, analysis_range: Option<(f32, f32)>) -> bool {
+        let mut open = self.open;
+        let mut changed = false;
+
+        egui::Window::new("Sections").open(&mut open).show(ctx, |ui| {
+            let analysis_range = match analysis_range {
+                Some(r) => r,
+                None => {
+                    ui.label("Load a run to add sections to it");
+                    return;
+                }
+            };
+
+            ui.label("sections");
+            let mut remove = None;
+            for (i, section) in annotations.sections.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    self.unsaved |= ui.add(egui::TextEdit::singleline(&mut section.name).desired_width(120.0)).changed();
+                    self.unsaved |= ui.add(egui::DragValue::new(&mut section.start).clamp_range(0.0..=f32::MAX).speed(0.1).suffix(" s")).changed();
+                    self.unsaved |= ui.add(egui::DragValue::new(&mut section.end).clamp_range(0.0..=f32::MAX).speed(0.1).suffix(" s")).changed();
+
+                    let analysed = analysed_section.as_deref() == Some(section.name.as_str());
+                    if ui.selectable_label(analysed, "analyse").clicked() {
+                        *analysed_section = if analysed { None } else { Some(section.name.clone()) };
+                        changed = true;
+                    }
+                    if ui.button("show").clicked() {
+                        SuspensionGraph::request_zoom(ctx, section.start as f64 - ZOOM_PADDING, section.end as f64 + ZOOM_PADDING);
+                    }
+                    if ui.button("remove").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                annotations.sections.remove(i);
+                self.unsaved = true;
+            }
+            if ui.button("Add section from analysis range").clicked() {
+                annotations.sections.push(Section {
+                    name: format!("section {}", annotations.sections.len() + 1),
+                    start: analysis_range.0,
+                    end: analysis_range.1,
+                });
+                self.unsaved = true;
+            }
+
+            ui.separator();
+
+            ui.label("markers");
+            let mut remove = None;
+            for (i, marker) in annotations.markers.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    self.unsaved |= ui.add(egui::TextEdit::singleline(&mut marker.name).desired_width(120.0)).changed();
+                    self.unsaved |= ui.add(egui::DragValue::new(&mut marker.time).clamp_range(0.0..=f32::MAX).speed(0.1).suffix(" s")).changed();
+                    if ui.button("show").clicked() {
+                        SuspensionGraph::request_zoom(ctx, marker.time as f64 - ZOOM_PADDING, marker.time as f64 + ZOOM_PADDING);
+                    }
+                    if ui.button("remove").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                annotations.markers.remove(i);
+                self.unsaved = true;
+            }
+            if ui.button("Add marker at start of analysis range").clicked() {
+                annotations.markers.push(Marker {
+                    name: format!("marker {}", annotations.markers.len() + 1),
+                    time: analysis_range.0,
+                });
+                self.unsaved = true;
+            }
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    match annotations.save(run_path) {
+                        Ok(()) => {
+                            self.unsaved = false;
+                            self.error = None;
+                            changed = true;
+                        }
+                        Err(e) => self.error = Some(e),
+                    }
+                }
+                if self.unsaved {
+                    ui.label("unsaved changes");
+                }
+            });
+            if let Some(error) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+        });
+
+        self.open = open;
+        changed
+    }
+
+    /// Forget about unsaved edits, called when a new run replaces the annotations
+    ///
+    /// # Arguments
+    ///
+    /// `load_error`: Why the annotations of the new run could not be loaded, the window is opened to show it
+    pub fn reset(&mut self, load_error: Option<String>) {
+        self.unsaved = false;
+        if load_error.is_some() {
+            self.open = true;
+        }
+        self.error = load_error;
+    }
+}
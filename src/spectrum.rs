@@ -0,0 +1,228 @@
+use std::f64::consts::PI;
+
+/// Power of each frequency in a series of overlapping segments of a channel
+pub struct Spectrogram {
+    /// Time of the centre of each segment in seconds
+    pub times: Vec<f32>,
+    /// Frequency of each bin in Hz
+    pub frequencies: Vec<f32>,
+    /// Power spectral density of each segment, indexed [segment][frequency bin], in units² per Hz
+    pub power: Vec<Vec<f32>>,
+}
+
+/// In place radix 2 fast Fourier transform, the length must be a power of two
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+
+    // bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_re, w_im) = ((angle * k as f64).cos(), (angle * k as f64).sin());
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Hann window of `len` samples
+pub fn hann_window(len: usize) -> Vec<f64> {
+    if len < 2 {
+        return vec![1.0; len];
+    }
+    (0..len).map(|i| 0.5 - 0.5 * (2.0 * PI * i as f64 / (len - 1) as f64).cos()).collect()
+}
+
+/// One sided power spectral density of a single segment, which is zero padded to a power of two
+///
+/// The mean is removed first so the travel offset does not swamp the low frequencies.
+///
+/// # Returns
+///
+/// The density of each bin from 0 Hz to the Nyquist frequency, in units² per Hz
+fn segment_psd(segment: &[f32], sample_rate: f32) -> Vec<f32> {
+    let len = segment.len();
+    let padded_len = len.next_power_of_two();
+    let window = hann_window(len);
+    let mean = segment.iter().map(|s| *s as f64).sum::<f64>() / len as f64;
+
+    let mut re = vec![0.0; padded_len];
+    let mut im = vec![0.0; padded_len];
+    for (i, s) in segment.iter().enumerate() {
+        re[i] = (*s as f64 - mean) * window[i];
+    }
+    fft(&mut re, &mut im);
+
+    // scale so the density integrates to the variance of the windowed signal
+    let window_power: f64 = window.iter().map(|w| w * w).sum();
+    let scale = 1.0 / (sample_rate as f64 * window_power);
+    (0..=padded_len / 2)
+        .map(|k| {
+            let density = (re[k] * re[k] + im[k] * im[k]) * scale;
+            // every bin but DC and Nyquist holds the power of the matching negative frequency too
+            let one_sided = if k == 0 || k == padded_len / 2 { density } else { 2.0 * density };
+            one_sided as f32
+        })
+        .collect()
+}
+
+/// Frequencies of the bins `segment_psd` gives for a segment of `segment_len` samples
+fn bin_frequencies(segment_len: usize, sample_rate: f32) -> Vec<f32> {
+    let padded_len = segment_len.next_power_of_two();
+    (0..=padded_len / 2).map(|k| k as f32 * sample_rate / padded_len as f32).collect()
+}
+
+/// Magnitude spectrum of a whole channel with a Hann window
+///
+/// # Returns
+///
+/// (frequency in Hz, RMS amplitude in the bin in the units of the channel) pairs, empty if there are fewer than two
+/// samples
+pub fn magnitude_spectrum(data: &[f32], sample_rate: f32) -> Vec<(f32, f32)> {
+    if data.len() < 2 {
+        return Vec::new();
+    }
+
+    // amplitude is the square root of the power in a bin
+    let bin_width = sample_rate / data.len().next_power_of_two() as f32;
+    bin_frequencies(data.len(), sample_rate).into_iter()
+        .zip(segment_psd(data, sample_rate))
+        .map(|(f, p)| (f, (p * bin_width).sqrt()))
+        .collect()
+}
+
+/// Start index of every segment of `segment_len` samples that fits in `data_len` samples, overlapping by `overlap`
+fn segment_starts(data_len: usize, segment_len: usize, overlap: f32) -> Vec<usize> {
+    let hop = usize::max((segment_len as f32 * (1.0 - overlap.clamp(0.0, 0.95))) as usize, 1);
+    if segment_len == 0 || data_len < segment_len {
+        return Vec::new();
+    }
+    (0..=data_len - segment_len).step_by(hop).collect()
+}
+
+/// Welch power spectral density, the average of the densities of overlapping windowed segments
+///
+/// # Arguments
+///
+/// `data`: The channel samples
+/// `sample_rate`: The polling rate of the channel in Hz
+/// `segment_len`: Samples per segment, longer segments give finer frequency resolution but more noise
+/// `overlap`: Fraction of a segment shared with the next one
+///
+/// # Returns
+///
+/// (frequency in Hz, density in units² per Hz) pairs, empty if the data is shorter than a segment
+pub fn welch_psd(data: &[f32], sample_rate: f32, segment_len: usize, overlap: f32) -> Vec<(f32, f32)> {
+    let starts = segment_starts(data.len(), segment_len, overlap);
+    if starts.is_empty() {
+        return Vec::new();
+    }
+
+    let mut total = vec![0.0; segment_len.next_power_of_two() / 2 + 1];
+    for start in &starts {
+        for (t, p) in total.iter_mut().zip(segment_psd(&data[*start..*start + segment_len], sample_rate)) {
+            *t += p;
+        }
+    }
+
+    bin_frequencies(segment_len, sample_rate).into_iter()
+        .zip(total)
+        .map(|(f, t)| (f, t / starts.len() as f32))
+        .collect()
+}
+
+/// Power spectral density of each of a series of overlapping segments, showing how the spectrum changes over time
+///
+/// # Arguments
+///
+/// `data`: The channel samples
+/// `sample_rate`: The polling rate of the channel in Hz
+/// `segment_len`: Samples per segment
+/// `overlap`: Fraction of a segment shared with the next one
+/// `start_time`: Time of the first sample in seconds
+pub fn spectrogram(data: &[f32], sample_rate: f32, segment_len: usize, overlap: f32, start_time: f32) -> Spectrogram {
+    let starts = segment_starts(data.len(), segment_len, overlap);
+
+    Spectrogram {
+        times: starts.iter().map(|s| start_time + (*s as f32 + segment_len as f32 / 2.0) / sample_rate).collect(),
+        frequencies: bin_frequencies(segment_len, sample_rate),
+        power: starts.iter().map(|s| segment_psd(&data[*s..*s + segment_len], sample_rate)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: f32 = 1000.0;
+    /// 8 samples a cycle, so it falls on a bin centre for any power of two segment of at least 8 samples
+    const FREQUENCY: f32 = 125.0;
+    const AMPLITUDE: f32 = 3.0;
+
+    fn sine(len: usize) -> Vec<f32> {
+        (0..len).map(|i| AMPLITUDE * (2.0 * std::f32::consts::PI * FREQUENCY * i as f32 / RATE).sin()).collect()
+    }
+
+    fn peak(spectrum: &[(f32, f32)]) -> (f32, f32) {
+        spectrum.iter().copied().fold((0.0, f32::MIN), |best, bin| if bin.1 > best.1 { bin } else { best })
+    }
+
+    #[test]
+    fn fft_matches_dft() {
+        let input = [1.0, -2.0, 0.5, 3.0, 0.0, -1.0, 2.5, 1.5];
+        let (mut re, mut im) = (input.to_vec(), vec![0.0; input.len()]);
+        fft(&mut re, &mut im);
+
+        let n = input.len() as f64;
+        for k in 0..input.len() {
+            let angle = |i: usize| -2.0 * PI * (k * i) as f64 / n;
+            let dft_re: f64 = input.iter().enumerate().map(|(i, x)| x * angle(i).cos()).sum();
+            let dft_im: f64 = input.iter().enumerate().map(|(i, x)| x * angle(i).sin()).sum();
+            assert!((re[k] - dft_re).abs() < 1e-9 && (im[k] - dft_im).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn welch_psd_of_sine_peaks_at_its_frequency_and_integrates_to_its_variance() {
+        let psd = welch_psd(&sine(8192), RATE, 1024, 0.5);
+
+        assert_eq!(peak(&psd).0, FREQUENCY);
+        // Parseval, the density summed over the bins is the variance A²/2
+        let bin_width = RATE / 1024.0;
+        let variance: f32 = psd.iter().map(|(_, p)| p * bin_width).sum();
+        assert!((variance - AMPLITUDE * AMPLITUDE / 2.0).abs() < 0.01 * AMPLITUDE * AMPLITUDE);
+    }
+
+    #[test]
+    fn magnitude_spectrum_of_sine_holds_its_rms_amplitude() {
+        let spectrum = magnitude_spectrum(&sine(4096), RATE);
+
+        assert_eq!(peak(&spectrum).0, FREQUENCY);
+        // the Hann window spreads the sine over the neighbouring bins, their RMS amplitudes add up in power
+        let rms: f32 = spectrum.iter().map(|(_, a)| a * a).sum::<f32>().sqrt();
+        assert!((rms - AMPLITUDE / 2.0_f32.sqrt()).abs() < 0.01 * AMPLITUDE);
+    }
+}
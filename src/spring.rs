@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+/// Pascals per psi, air spring pressures are set in psi
+const PA_PER_PSI: f32 = 6894.76;
+/// Atmospheric pressure in Pa
+const ATMOSPHERE: f32 = 101_325.0;
+/// Polytropic index of the air in the chamber, suspension strokes are fast enough to be close to adiabatic
+const AIR_GAMMA: f32 = 1.4;
+/// Smallest share of the chamber volume the model compresses to, stops the force going to infinity when the
+/// chamber is set up smaller than the stroke
+const MIN_VOLUME_SHARE: f32 = 0.05;
+/// Number of deepest compressions the force usage is averaged over
+pub const DEEPEST_COMPRESSIONS: usize = 5;
+
+/// The spring of one end of a bike, forces are at the shock (or fork) against its stroke
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SpringModel {
+    /// Linear coil spring
+    Coil {
+        /// Spring rate in N/mm
+        rate: f32,
+        /// Compression of the spring at top out in mm
+        preload: f32,
+    },
+    /// Single positive air chamber, the negative chamber is left out
+    Air {
+        /// Pressure at top out in psi
+        pressure: f32,
+        /// Volume of the chamber at top out in cm³, without tokens
+        volume: f32,
+        /// Area of the air piston in mm²
+        piston_area: f32,
+        /// Number of volume spacers in the chamber
+        tokens: u32,
+        /// Volume taken by each spacer in cm³
+        token_volume: f32,
+    },
+}
+
+impl SpringModel {
+    /// The spring models in the order they are shown in the UI, with sensible default parameters
+    pub fn defaults() -> [SpringModel; 2] {
+        [
+            SpringModel::Coil { rate: 80.0, preload: 2.0 },
+            SpringModel::Air { pressure: 200.0, volume: 150.0, piston_area: 700.0, tokens: 0, token_volume: 10.0 },
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpringModel::Coil { .. } => "Coil",
+            SpringModel::Air { .. } => "Air",
+        }
+    }
+
+    /// Spring force at a point of the stroke
+    ///
+    /// # Arguments
+    ///
+    /// `stroke_mm`: The shock stroke from top out in mm
+    ///
+    /// # Returns
+    ///
+    /// The force in N
+    pub fn force(&self, stroke_mm: f32) -> f32 {
+        match *self {
+            SpringModel::Coil { rate, preload } => rate * (stroke_mm + preload),
+            SpringModel::Air { pressure, volume, piston_area, tokens, token_volume } => {
+                // volumes in mm³ so the swept volume is the piston area times the stroke
+                let chamber = (volume - tokens as f32 * token_volume) * 1000.0;
+                if chamber <= 0.0 {
+                    return 0.0;
+                }
+                let compressed = f32::max(chamber - piston_area * stroke_mm, chamber * MIN_VOLUME_SHARE);
+                let absolute = (pressure * PA_PER_PSI + ATMOSPHERE) * (chamber / compressed).powf(AIR_GAMMA);
+                // the outside of the piston sees atmospheric pressure, mm² to m² for the force
+                (absolute - ATMOSPHERE) * piston_area * 1e-6
+            }
+        }
+    }
+
+    /// Force against stroke from top out to full stroke
+    ///
+    /// # Returns
+    ///
+    /// (stroke in mm, force in N) pairs
+    pub fn curve(&self, full_stroke: f32, steps: usize) -> Vec<(f32, f32)> {
+        (0..=steps)
+            .map(|i| {
+                let stroke = full_stroke * i as f32 / steps as f32;
+                (stroke, self.force(stroke))
+            })
+            .collect()
+    }
+}
+
+/// How much of the force the spring can give was used in the deepest compressions
+///
+/// # Arguments
+///
+/// `spring`: The spring of the end
+/// `peaks`: The shock stroke in mm at the end of each compression
+/// `full_stroke`: The full shock stroke in mm
+///
+/// # Returns
+///
+/// (mean force of the `DEEPEST_COMPRESSIONS` deepest compressions in N, that force as % of the force at full stroke),
+/// None if there are no compressions
+pub fn force_usage(spring: &SpringModel, peaks: &[f32], full_stroke: f32) -> Option<(f32, f32)> {
+    if peaks.is_empty() {
+        return None;
+    }
+
+    let mut deepest = peaks.to_vec();
+    deepest.sort_by(|a, b| b.total_cmp(a));
+    deepest.truncate(DEEPEST_COMPRESSIONS);
+    let force = deepest.iter().map(|p| spring.force(*p)).sum::<f32>() / deepest.len() as f32;
+    let available = spring.force(full_stroke);
+
+    Some((force, if available > 0.0 { 100.0 * force / available } else { 0.0 }))
+}
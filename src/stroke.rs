@@ -0,0 +1,279 @@
+use serde::{Deserialize, Serialize};
+
+/// Time span in seconds the stroke velocity is measured over, stops single sample noise dominating the peak velocity
+pub const VELOCITY_SPAN: f32 = 0.005;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StrokeDirection {
+    /// Travel increasing, the suspension is being compressed
+    Compression,
+    /// Travel decreasing, the suspension is extending
+    Rebound,
+}
+
+/// A single movement of the suspension between two turning points
+#[derive(Clone, Copy, Debug)]
+pub struct Stroke {
+    /// Index of the sample the stroke starts at
+    pub start_index: usize,
+    /// Index of the sample the stroke ends at
+    pub end_index: usize,
+    /// Time the stroke starts at in seconds
+    pub start: f32,
+    /// Time the stroke ends at in seconds
+    pub end: f32,
+    /// The deepest travel reached during the stroke
+    pub peak: f32,
+    /// Change in travel between the start and the end of the stroke, always positive
+    pub amplitude: f32,
+    /// Length of the stroke in seconds
+    pub duration: f32,
+    /// Highest speed of the suspension during the stroke in travel units per second, always positive
+    pub peak_velocity: f32,
+    pub direction: StrokeDirection,
+}
+
+/// Finds compression and rebound strokes in a travel channel
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct StrokeDetector {
+    /// How far the travel has to move back from an extreme before it counts as a turning point, in % of travel in
+    /// the config
+    pub hysteresis: f32,
+    /// Strokes with a smaller amplitude than this are discarded, in % of travel in the config
+    pub min_amplitude: f32,
+}
+
+impl Default for StrokeDetector {
+    fn default() -> StrokeDetector {
+        StrokeDetector {
+            hysteresis: 2.0,
+            min_amplitude: 5.0,
+        }
+    }
+}
+
+impl StrokeDetector {
+    pub fn new(hysteresis: f32, min_amplitude: f32) -> StrokeDetector {
+        StrokeDetector {
+            hysteresis,
+            min_amplitude,
+        }
+    }
+
+    /// The detector with its distances converted out of % of travel
+    ///
+    /// # Arguments
+    ///
+    /// `convert`: Converts a travel in % to the new unit, must be increasing
+    pub fn converted(&self, convert: impl Fn(f32) -> f32) -> StrokeDetector {
+        StrokeDetector {
+            hysteresis: convert(self.hysteresis) - convert(0.0),
+            min_amplitude: convert(self.min_amplitude) - convert(0.0),
+        }
+    }
+
+    /// Find the indices of the turning points in a travel channel
+    ///
+    /// A maximum or minimum only becomes a turning point once the travel has moved `hysteresis` back
+    /// from it, so noise smaller than the hysteresis is ignored. The extreme the travel leaves from at the
+    /// start of the data counts as a turning point, the unfinished extreme at the end does not.
+    ///
+    /// # Arguments
+    ///
+    /// `data`: The travel samples
+    ///
+    /// # Returns
+    ///
+    /// The indices of the turning points in ascending order, alternating between maxima and minima
+    pub fn turning_points(&self, data: &[f32]) -> Vec<usize> {
+        let mut turning_points = Vec::new();
+        if data.len() < 3 {
+            return turning_points;
+        }
+
+        let hysteresis = self.hysteresis.max(0.0);
+        // None until the data first moves far enough to know which way it is heading
+        let mut rising: Option<bool> = None;
+        let (mut min_i, mut max_i) = (0, 0);
+        let mut extreme_i = 0;
+
+        for (i, val) in data.iter().enumerate() {
+            match rising {
+                None => {
+                    if *val < data[min_i] {
+                        min_i = i;
+                    }
+                    if *val > data[max_i] {
+                        max_i = i;
+                    }
+
+                    if *val > data[min_i] + hysteresis {
+                        rising = Some(true);
+                        turning_points.push(min_i);
+                        extreme_i = i;
+                    } else if *val < data[max_i] - hysteresis {
+                        rising = Some(false);
+                        turning_points.push(max_i);
+                        extreme_i = i;
+                    }
+                }
+                Some(true) => {
+                    if *val > data[extreme_i] {
+                        extreme_i = i;
+                    } else if *val < data[extreme_i] - hysteresis {
+                        turning_points.push(extreme_i);
+                        rising = Some(false);
+                        extreme_i = i;
+                    }
+                }
+                Some(false) => {
+                    if *val < data[extreme_i] {
+                        extreme_i = i;
+                    } else if *val > data[extreme_i] + hysteresis {
+                        turning_points.push(extreme_i);
+                        rising = Some(true);
+                        extreme_i = i;
+                    }
+                }
+            }
+        }
+
+        turning_points
+    }
+
+    /// Find the strokes in a travel channel
+    ///
+    /// # Arguments
+    ///
+    /// `data`: The travel samples
+    /// `polling_rate`: The polling rate of the channel in Hz
+    ///
+    /// # Returns
+    ///
+    /// The strokes between each pair of turning points with an amplitude of at least `min_amplitude`
+    pub fn detect(&self, data: &[f32], polling_rate: f32) -> Vec<Stroke> {
+        let turning_points = self.turning_points(data);
+        let velocity_span = usize::max((VELOCITY_SPAN * polling_rate / 2.0).round() as usize, 1);
+
+        turning_points
+            .windows(2)
+            .filter_map(|pair| {
+                let (start_index, end_index) = (pair[0], pair[1]);
+                let (start_val, end_val) = (data[start_index], data[end_index]);
+                let amplitude = (end_val - start_val).abs();
+
+                if amplitude < self.min_amplitude {
+                    return None;
+                }
+
+                let peak_velocity = (start_index..=end_index)
+                    .map(|i| {
+                        let low = i.saturating_sub(velocity_span);
+                        let high = usize::min(i + velocity_span, data.len() - 1);
+                        (data[high] - data[low]).abs() * polling_rate / (high - low) as f32
+                    })
+                    .fold(0.0, f32::max);
+
+                let direction = if end_val > start_val {
+                    StrokeDirection::Compression
+                } else {
+                    StrokeDirection::Rebound
+                };
+
+                Some(Stroke {
+                    start_index,
+                    end_index,
+                    start: start_index as f32 / polling_rate,
+                    end: end_index as f32 / polling_rate,
+                    peak: f32::max(start_val, end_val),
+                    amplitude,
+                    duration: (end_index - start_index) as f32 / polling_rate,
+                    peak_velocity,
+                    direction,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::wave_gen::WaveGen;
+
+    const RATE: f32 = 1000.0;
+
+    #[test]
+    fn sine_wave_gives_alternating_strokes() {
+        // starts at the middle of the travel and rises, peaks at samples 1571 and 7854, trough at 4712
+        let wave = WaveGen::new(300, 0).generate();
+        let strokes = StrokeDetector::new(2.0, 5.0).detect(&wave, RATE);
+
+        assert_eq!(strokes.len(), 3);
+        assert_eq!(strokes[0].direction, StrokeDirection::Compression);
+        assert_eq!(strokes[1].direction, StrokeDirection::Rebound);
+        assert_eq!(strokes[2].direction, StrokeDirection::Compression);
+        assert_eq!(strokes[0].start_index, 0);
+        assert!((strokes[0].amplitude - 300.0).abs() < 1.0);
+
+        for stroke in &strokes[1..] {
+            assert!((stroke.amplitude - 600.0).abs() < 1.0);
+            assert!((stroke.peak - 600.0).abs() < 1.0);
+            assert!((stroke.duration - std::f32::consts::PI).abs() < 0.01);
+            // the steepest part of the wave moves 300 * 1/1000 per sample
+            assert!((stroke.peak_velocity - 300.0).abs() < 1.0);
+        }
+        assert!((strokes[1].start - 1.571).abs() < 0.01);
+        assert_eq!(strokes[1].end_index, strokes[2].start_index);
+    }
+
+    #[test]
+    fn offset_wave_shifts_strokes_earlier() {
+        let wave = WaveGen::new(300, 0).generate();
+        let offset_wave = WaveGen::new(300, 100).generate();
+        let detector = StrokeDetector::default();
+
+        let strokes = detector.detect(&wave, RATE);
+        let offset_strokes = detector.detect(&offset_wave, RATE);
+
+        assert_eq!(strokes.len(), offset_strokes.len());
+        // both waves start rising from the first sample, the strokes after that are 100 samples apart
+        for (stroke, offset_stroke) in strokes.iter().zip(offset_strokes.iter()).skip(1) {
+            assert!((stroke.start - offset_stroke.start - 0.1).abs() < 0.002);
+        }
+    }
+
+    #[test]
+    fn min_amplitude_discards_small_strokes() {
+        let wave = WaveGen::new(300, 0).generate();
+
+        assert!(StrokeDetector::new(2.0, 601.0).detect(&wave, RATE).is_empty());
+        assert_eq!(StrokeDetector::new(2.0, 599.0).detect(&wave, RATE).len(), 2);
+        assert_eq!(StrokeDetector::new(2.0, 299.0).detect(&wave, RATE).len(), 3);
+    }
+
+    #[test]
+    fn hysteresis_ignores_noise() {
+        let noisy_wave: Vec<f32> = WaveGen::new(300, 0)
+            .generate()
+            .iter()
+            .enumerate()
+            .map(|(i, v)| if i % 2 == 0 { v + 3.0 } else { v - 3.0 })
+            .collect();
+
+        assert_eq!(StrokeDetector::new(10.0, 5.0).detect(&noisy_wave, RATE).len(), 3);
+        // without enough hysteresis every sample is a turning point
+        assert!(StrokeDetector::new(1.0, 5.0).turning_points(&noisy_wave).len() > 1000);
+    }
+
+    #[test]
+    fn short_and_flat_data_gives_no_strokes() {
+        let detector = StrokeDetector::default();
+
+        for len in 0..6 {
+            let short = WaveGen::new(300, 0).generate()[..len].to_vec();
+            assert!(detector.detect(&short, RATE).is_empty());
+        }
+        assert!(detector.detect(&vec![50.0; 1000], RATE).is_empty());
+    }
+}
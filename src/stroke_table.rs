@@ -0,0 +1,193 @@
+use egui::{Context, Sense, Ui, Vec2};
+
+use crate::data::Data;
+use crate::graph::suspension_graph::SuspensionGraph;
+use crate::stroke::{Stroke, StrokeDirection};
+
+const COLUMN_WIDTH: f32 = 90.0;
+const ROW_HEIGHT: f32 = 18.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum StrokeColumn {
+    Time,
+    End,
+    Direction,
+    Amplitude,
+    PeakVelocity,
+    Duration,
+}
+
+impl StrokeColumn {
+    fn all() -> [StrokeColumn; 6] {
+        [
+            StrokeColumn::Time,
+            StrokeColumn::End,
+            StrokeColumn::Direction,
+            StrokeColumn::Amplitude,
+            StrokeColumn::PeakVelocity,
+            StrokeColumn::Duration,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            StrokeColumn::Time => "Time (s)",
+            StrokeColumn::End => "End",
+            StrokeColumn::Direction => "Direction",
+            StrokeColumn::Amplitude => "Amplitude",
+            StrokeColumn::PeakVelocity => "Peak velocity",
+            StrokeColumn::Duration => "Duration (s)",
+        }
+    }
+}
+
+/// Window listing every compression and rebound stroke of the run, clicking a row zooms the suspension graph to it
+pub struct StrokeTable {
+    pub open: bool,
+    sort_column: StrokeColumn,
+    sort_descending: bool,
+    min_amplitude: f32,
+    min_velocity: f32,
+    show_compressions: bool,
+    show_rebounds: bool,
+}
+
+impl StrokeTable {
+    pub fn new() -> StrokeTable {
+        StrokeTable {
+            open: false,
+            sort_column: StrokeColumn::Time,
+            sort_descending: false,
+            min_amplitude: 0.0,
+            min_velocity: 0.0,
+            show_compressions: true,
+            show_rebounds: true,
+        }
+    }
+
+    pub fn update(&mut self, ctx: &Context, data: &Data) {
+        let mut open = self.open;
+
+        egui::Window::new("Strokes")
+            .open(&mut open)
+            .default_width(COLUMN_WIDTH * 6.0)
+            .show(ctx, |ui| {
+                self.draw_filters(ui);
+                ui.separator();
+                self.draw_table(ctx, ui, data);
+            });
+
+        self.open = open;
+    }
+
+    fn draw_filters(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_compressions, "compressions");
+            ui.checkbox(&mut self.show_rebounds, "rebounds");
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut self.min_amplitude).clamp_range(0.0..=f32::MAX).prefix("min amplitude: "));
+            ui.add(egui::DragValue::new(&mut self.min_velocity).clamp_range(0.0..=f32::MAX).prefix("min velocity: "));
+        });
+    }
+
+    fn draw_table(&mut self, ctx: &Context, ui: &mut Ui, data: &Data) {
+        let rows = self.filtered_sorted_strokes(data);
+
+        ui.label(format!("{} strokes", rows.len()));
+
+        ui.horizontal(|ui| {
+            for column in StrokeColumn::all() {
+                let mut text = column.name().to_string();
+                if column == self.sort_column {
+                    text += if self.sort_descending { " v" } else { " ^" };
+                }
+
+                if ui.add_sized([COLUMN_WIDTH, ROW_HEIGHT], egui::Button::new(text)).clicked() {
+                    if column == self.sort_column {
+                        self.sort_descending ^= true;
+                    } else {
+                        self.sort_column = column;
+                        // biggest hits first is what you normally want when sorting by a size
+                        self.sort_descending = !matches!(column, StrokeColumn::Time | StrokeColumn::End | StrokeColumn::Direction);
+                    }
+                }
+            }
+        });
+
+        egui::ScrollArea::vertical()
+            .max_height(400.0)
+            .show_rows(ui, ROW_HEIGHT, rows.len(), |ui, row_range| {
+                for (end, stroke) in &rows[row_range] {
+                    let row = ui.horizontal(|ui| {
+                        let direction = match stroke.direction {
+                            StrokeDirection::Compression => "compression",
+                            StrokeDirection::Rebound => "rebound",
+                        };
+
+                        for text in [
+                            format!("{:.3}", stroke.start),
+                            end.to_string(),
+                            direction.to_string(),
+                            format!("{:.1}", stroke.amplitude),
+                            format!("{:.1}", stroke.peak_velocity),
+                            format!("{:.3}", stroke.duration),
+                        ] {
+                            ui.add_sized(Vec2::new(COLUMN_WIDTH, ROW_HEIGHT), egui::Label::new(text));
+                        }
+                    });
+
+                    let row_response = row.response.interact(Sense::click());
+                    if row_response.hovered() {
+                        ui.painter().rect_stroke(row_response.rect, 2.0, ui.visuals().widgets.hovered.bg_stroke);
+                    }
+                    if row_response.clicked() {
+                        // show some of the travel either side so the stroke can be seen in context
+                        let padding = f64::max(stroke.duration as f64, 0.05);
+                        SuspensionGraph::request_zoom(ctx, stroke.start as f64 - padding, stroke.end as f64 + padding);
+                    }
+                }
+            });
+    }
+
+    /// The strokes from both ends which pass the filters, sorted by the selected column
+    fn filtered_sorted_strokes(&self, data: &Data) -> Vec<(&'static str, Stroke)> {
+        let mut rows = Vec::new();
+
+        for (end, field) in [("Rear", "rear_strokes"), ("Front", "front_strokes")] {
+            if let Some(strokes) = data.get_strokes(field.to_string()) {
+                rows.extend(strokes.iter().filter(|s| self.passes_filters(s)).map(|s| (end, *s)));
+            }
+        }
+
+        rows.sort_by(|(end_a, a), (end_b, b)| {
+            let ordering = match self.sort_column {
+                StrokeColumn::Time => a.start.total_cmp(&b.start),
+                StrokeColumn::End => end_a.cmp(end_b).then(a.start.total_cmp(&b.start)),
+                StrokeColumn::Direction => (a.direction == StrokeDirection::Rebound)
+                    .cmp(&(b.direction == StrokeDirection::Rebound))
+                    .then(a.start.total_cmp(&b.start)),
+                StrokeColumn::Amplitude => a.amplitude.total_cmp(&b.amplitude),
+                StrokeColumn::PeakVelocity => a.peak_velocity.total_cmp(&b.peak_velocity),
+                StrokeColumn::Duration => a.duration.total_cmp(&b.duration),
+            };
+
+            if self.sort_descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+
+        rows
+    }
+
+    fn passes_filters(&self, stroke: &Stroke) -> bool {
+        let direction_shown = match stroke.direction {
+            StrokeDirection::Compression => self.show_compressions,
+            StrokeDirection::Rebound => self.show_rebounds,
+        };
+
+        direction_shown && stroke.amplitude >= self.min_amplitude && stroke.peak_velocity >= self.min_velocity
+    }
+}
@@ -0,0 +1,139 @@
+use crate::config_info::TravelThresholds;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TravelEventKind {
+    /// The suspension went past the bottom out threshold
+    BottomOut,
+    /// The suspension extended past the top out threshold
+    TopOut,
+}
+
+/// A period where the travel was past one of the thresholds
+#[derive(Clone, Copy, Debug)]
+pub struct TravelEvent {
+    pub kind: TravelEventKind,
+    /// Time the threshold was crossed at in seconds
+    pub start: f32,
+    /// Time the travel came back past the threshold and hysteresis in seconds
+    pub end: f32,
+    /// Time of the most extreme travel during the event in seconds
+    pub peak_time: f32,
+    /// The most extreme travel during the event
+    pub peak: f32,
+}
+
+/// Find the bottom outs and top outs in a travel channel
+///
+/// An event starts when the travel crosses a threshold and only ends once it has come back by more than the
+/// hysteresis, so a noisy signal sitting on a threshold is counted once.
+///
+/// # Arguments
+///
+/// `data`: The travel samples, in % of travel
+/// `polling_rate`: The polling rate of the channel in Hz
+/// `thresholds`: The thresholds for this end of the bike
+///
+/// # Returns
+///
+/// The events in the order they started, an event still going at the end of the data ends at the last sample
+pub fn detect_travel_events(data: &[f32], polling_rate: f32, thresholds: &TravelThresholds) -> Vec<TravelEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<TravelEvent> = None;
+    let hysteresis = thresholds.hysteresis.max(0.0);
+
+    for (i, val) in data.iter().enumerate() {
+        let time = i as f32 / polling_rate;
+
+        if let Some(event) = current.as_mut() {
+            let (more_extreme, finished) = match event.kind {
+                TravelEventKind::BottomOut => (*val > event.peak, *val < thresholds.bottom_out - hysteresis),
+                TravelEventKind::TopOut => (*val < event.peak, *val > thresholds.top_out + hysteresis),
+            };
+
+            if more_extreme {
+                event.peak = *val;
+                event.peak_time = time;
+            }
+            if finished {
+                event.end = time;
+                events.push(*event);
+                current = None;
+            }
+            continue;
+        }
+
+        let kind = if *val >= thresholds.bottom_out {
+            TravelEventKind::BottomOut
+        } else if *val <= thresholds.top_out {
+            TravelEventKind::TopOut
+        } else {
+            continue;
+        };
+
+        current = Some(TravelEvent {
+            kind,
+            start: time,
+            end: time,
+            peak_time: time,
+            peak: *val,
+        });
+    }
+
+    if let Some(mut event) = current {
+        event.end = (data.len() - 1) as f32 / polling_rate;
+        events.push(event);
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: f32 = 10.0;
+
+    /// Bottom out at 95 %, top out at 2 %, 3 % hysteresis
+    fn detect(data: &[f32]) -> Vec<TravelEvent> {
+        detect_travel_events(data, RATE, &TravelThresholds::default())
+    }
+
+    #[test]
+    fn noise_on_the_threshold_is_one_event() {
+        let events = detect(&[50.0, 94.0, 96.0, 94.0, 96.0, 93.0, 96.0, 50.0]);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, TravelEventKind::BottomOut);
+        assert!((events[0].start - 0.2).abs() < 1e-6);
+        assert!((events[0].end - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn peak_is_the_most_extreme_sample() {
+        let events = detect(&[50.0, 96.0, 99.0, 97.0, 50.0, 1.0, 0.5, 1.5, 10.0]);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, TravelEventKind::BottomOut);
+        assert_eq!(events[0].peak, 99.0);
+        assert!((events[0].peak_time - 0.2).abs() < 1e-6);
+        assert_eq!(events[1].kind, TravelEventKind::TopOut);
+        assert_eq!(events[1].peak, 0.5);
+        assert!((events[1].peak_time - 0.6).abs() < 1e-6);
+        assert!((events[1].end - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn open_event_ends_at_the_last_sample() {
+        let events = detect(&[50.0, 96.0, 98.0]);
+
+        assert_eq!(events.len(), 1);
+        assert!((events[0].start - 0.1).abs() < 1e-6);
+        assert!((events[0].end - 0.2).abs() < 1e-6);
+        assert_eq!(events[0].peak, 98.0);
+    }
+
+    #[test]
+    fn empty_data_has_no_events() {
+        assert!(detect(&[]).is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+use serde::Serialize;
+
+/// Share of full travel above which the time spent deep in the travel is counted
+pub const DEEP_TRAVEL: f32 = 0.8;
+/// Share of full travel above which the time spent near bottoming out is counted
+pub const NEAR_BOTTOM_OUT: f32 = 0.95;
+
+/// Summary of the travel used by one end over the analysis range
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct TravelStats {
+    pub mean: f32,
+    pub median: f32,
+    pub p5: f32,
+    pub p25: f32,
+    pub p75: f32,
+    pub p95: f32,
+    pub max: f32,
+    pub std_dev: f32,
+    /// Seconds spent above `DEEP_TRAVEL` of full travel
+    pub time_above_80: f32,
+    /// Seconds spent above `NEAR_BOTTOM_OUT` of full travel
+    pub time_above_95: f32,
+    /// Length of the data the statistics are taken over in seconds
+    pub duration: f32,
+}
+
+impl TravelStats {
+    /// Names of the columns written by `csv_row`, in order
+    pub const CSV_COLUMNS: [&'static str; 11] = [
+        "mean", "median", "p5", "p25", "p75", "p95", "max", "std_dev", "time_above_80_s", "time_above_95_s", "duration_s",
+    ];
+
+    /// Measure the travel of one end
+    ///
+    /// # Arguments
+    ///
+    /// `data`: The travel over the analysis range
+    /// `polling_rate`: Polling rate of the channel
+    /// `full_travel`: The travel at full compression in the same unit, the time above thresholds is measured against it
+    ///
+    /// # Returns
+    ///
+    /// The statistics, None if there is no data
+    pub fn compute(data: &[f32], polling_rate: f32, full_travel: f32) -> Option<TravelStats> {
+        if data.is_empty() {
+            return None;
+        }
+
+        let mut sorted = data.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |fraction: f32| sorted[((sorted.len() - 1) as f32 * fraction).round() as usize];
+
+        let len = data.len() as f64;
+        let mean = data.iter().map(|d| *d as f64).sum::<f64>() / len;
+        let variance = data.iter().map(|d| (*d as f64 - mean).powi(2)).sum::<f64>() / len;
+        let time_above = |share: f32| data.iter().filter(|d| **d > full_travel * share).count() as f32 / polling_rate;
+
+        Some(TravelStats {
+            mean: mean as f32,
+            median: percentile(0.5),
+            p5: percentile(0.05),
+            p25: percentile(0.25),
+            p75: percentile(0.75),
+            p95: percentile(0.95),
+            max: sorted[sorted.len() - 1],
+            std_dev: variance.sqrt() as f32,
+            time_above_80: time_above(DEEP_TRAVEL),
+            time_above_95: time_above(NEAR_BOTTOM_OUT),
+            duration: data.len() as f32 / polling_rate,
+        })
+    }
+
+    /// The values in the order of `CSV_COLUMNS`
+    pub fn csv_row(&self) -> [f32; 11] {
+        [
+            self.mean, self.median, self.p5, self.p25, self.p75, self.p95, self.max, self.std_dev,
+            self.time_above_80, self.time_above_95, self.duration,
+        ]
+    }
+}
+
+/// The statistics of one end with the unit they are in, as written to the JSON export
+#[derive(Serialize)]
+struct ExportedStats<'a> {
+    end: &'a str,
+    unit: &'a str,
+    #[serde(flatten)]
+    stats: &'a TravelStats,
+}
+
+/// CSV with a header line and a line per end
+///
+/// # Arguments
+///
+/// `ends`: (end name, statistics) pairs
+/// `unit`: The unit of the travel values
+pub fn to_csv(ends: &[(&str, TravelStats)], unit: &str) -> String {
+    let mut csv = "end,unit,".to_string() + &TravelStats::CSV_COLUMNS.join(",") + "\n";
+    for (end, stats) in ends {
+        let values: Vec<String> = stats.csv_row().iter().map(|v| v.to_string()).collect();
+        csv += &format!("{},{},{}\n", end, unit, values.join(","));
+    }
+    csv
+}
+
+/// Pretty JSON array with an object per end
+///
+/// # Arguments
+///
+/// `ends`: (end name, statistics) pairs
+/// `unit`: The unit of the travel values
+pub fn to_json(ends: &[(&str, TravelStats)], unit: &str) -> String {
+    let exported: Vec<ExportedStats<'_>> = ends.iter().map(|(end, stats)| ExportedStats { end, unit, stats }).collect();
+    serde_json::to_string_pretty(&exported).expect("Error serialising travel statistics")
+}
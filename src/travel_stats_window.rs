@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::Path;
+
+use egui::Context;
+use rfd::FileDialog;
+
+use crate::data::Data;
+use crate::travel_stats::{self, TravelStats};
+
+/// Label of a row of the statistics grid and the statistic shown in it
+type StatRow = (&'static str, fn(&TravelStats) -> f32);
+
+/// Window showing the travel statistics of both ends, with buttons to export them
+pub struct TravelStatsWindow {
+    pub open: bool,
+    /// Why the last export failed, None if it worked
+    export_error: Option<String>,
+}
+
+impl TravelStatsWindow {
+    pub fn new() -> TravelStatsWindow {
+        TravelStatsWindow { open: false, export_error: None }
+    }
+
+    /// Draw the window
+    ///
+    /// # Arguments
+    ///
+    /// `ctx`: The eGui context
+    /// `data`: The run data holding the "rear_travel_stats" and "front_travel_stats" fields
+    /// `unit`: The unit the travel is shown in
+    /// `run_path`: The path of the loaded run, used to name the exported file
+    pub fn update(&mut self, ctx: &Context, data: &Data, unit: &str, run_path: &str) {
+        let mut open = self.open;
+
+        egui::Window::new("Travel statistics").open(&mut open).show(ctx, |ui| {
+            let ends: Vec<(&str, TravelStats)> = ["front", "rear"].into_iter()
+                .filter_map(|end| data.get_travel_stats(end.to_string() + "_travel_stats").map(|s| (end, *s)))
+                .collect();
+            if ends.is_empty() {
+                ui.label("Load a run to see the travel statistics");
+                return;
+            }
+
+            egui::Grid::new("travel_stats_grid").striped(true).show(ui, |ui| {
+                ui.label("");
+                for (end, _) in &ends {
+                    ui.label(*end);
+                }
+                ui.end_row();
+
+                let rows: [StatRow; 8] = [
+                    ("mean", |s| s.mean),
+                    ("median", |s| s.median),
+                    ("P5", |s| s.p5),
+                    ("P25", |s| s.p25),
+                    ("P75", |s| s.p75),
+                    ("P95", |s| s.p95),
+                    ("max", |s| s.max),
+                    ("std dev", |s| s.std_dev),
+                ];
+                for (label, value) in rows {
+                    ui.label(format!("{} ({})", label, unit));
+                    for (_, stats) in &ends {
+                        ui.label(format!("{:.1}", value(stats)));
+                    }
+                    ui.end_row();
+                }
+
+                let times: [StatRow; 2] = [("time > 80 %", |s| s.time_above_80), ("time > 95 %", |s| s.time_above_95)];
+                for (label, value) in times {
+                    ui.label(label);
+                    for (_, stats) in &ends {
+                        ui.label(format!("{:.2} s ({:.1} %)", value(stats), 100.0 * value(stats) / stats.duration));
+                    }
+                    ui.end_row();
+                }
+            });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui.button("Export CSV").clicked() {
+                    self.export_error = export(run_path, "csv", &travel_stats::to_csv(&ends, unit)).err();
+                }
+                if ui.button("Export JSON").clicked() {
+                    self.export_error = export(run_path, "json", &travel_stats::to_json(&ends, unit)).err();
+                }
+            });
+            if let Some(error) = &self.export_error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+        });
+
+        self.open = open;
+    }
+}
+
+/// Ask where to save the statistics, suggesting a file named after the run
+///
+/// # Returns
+///
+/// A message saying why the file could not be written on failure, cancelling the dialog is not a failure
+fn export(run_path: &str, extension: &str, contents: &str) -> Result<(), String> {
+    let run_path = Path::new(run_path.trim());
+    let run_name = run_path.file_stem().and_then(|s| s.to_str()).unwrap_or("run");
+    let mut dialog = FileDialog::new()
+        .add_filter(extension, &[extension])
+        .set_file_name(format!("{}_stats.{}", run_name, extension));
+    if let Some(dir) = run_path.parent() {
+        dialog = dialog.set_directory(dir);
+    }
+
+    match dialog.save_file() {
+        Some(path) => fs::write(path, contents).map_err(|e| format!("Error exporting travel statistics: {}", e)),
+        None => Ok(()),
+    }
+}
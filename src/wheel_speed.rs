@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+/// Converts m/s to km/h
+pub const MS_TO_KMH: f32 = 3.6;
+/// Longest time between pulses in seconds before the wheel counts as stopped
+const MAX_PULSE_PERIOD: f32 = 2.0;
+
+/// How a wheel speed channel records the pulses of the sensor, declared in the run file header in place of the
+/// remap reference, e.g. "WS:1000:pulse_count"
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PulseKind {
+    /// Number of pulses since the previous sample
+    Count,
+    /// Time in µs between the last two pulses, 0 before the first pulse
+    Period,
+}
+
+impl PulseKind {
+    /// The kind a header declares, None if the channel is not a wheel speed channel
+    pub fn from_header(declaration: &str) -> Option<PulseKind> {
+        match declaration {
+            "pulse_count" => Some(PulseKind::Count),
+            "pulse_period" => Some(PulseKind::Period),
+            _ => None,
+        }
+    }
+}
+
+/// The wheel speed sensor fitted to a bike
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct WheelSensor {
+    /// Rolling circumference of the wheel the sensor is on in mm
+    pub circumference: f32,
+    /// Magnets on the wheel, each passes the sensor once per revolution
+    pub pulses_per_revolution: u32,
+}
+
+impl Default for WheelSensor {
+    fn default() -> WheelSensor {
+        WheelSensor {
+            circumference: 2290.0,
+            pulses_per_revolution: 1,
+        }
+    }
+}
+
+impl WheelSensor {
+    /// Metres the wheel rolls between pulses
+    fn pulse_distance(&self) -> f32 {
+        self.circumference / 1000.0 / u32::max(self.pulses_per_revolution, 1) as f32
+    }
+
+    /// Speed of the wheel from a wheel speed channel
+    ///
+    /// # Arguments
+    ///
+    /// `data`: The samples of the wheel speed channel
+    /// `kind`: How the channel records the pulses
+    /// `polling_rate`: Polling rate of the channel
+    ///
+    /// # Returns
+    ///
+    /// The speed in m/s at each sample
+    pub fn speed(&self, data: &[f32], kind: PulseKind, polling_rate: f32) -> Vec<f32> {
+        let pulse_distance = self.pulse_distance();
+        match kind {
+            PulseKind::Count => {
+                // most samples hold no pulse, so the speed comes from the time between the samples that do and is
+                // held until the next one, dropping once the wheel has taken longer than that to turn
+                let mut speed = Vec::with_capacity(data.len());
+                let mut last_pulse: Option<usize> = None;
+                let mut current = 0.0;
+                for (i, count) in data.iter().enumerate() {
+                    if let Some(last) = last_pulse {
+                        let elapsed = (i - last) as f32 / polling_rate;
+                        if *count > 0.0 {
+                            current = count * pulse_distance / elapsed;
+                        } else if elapsed > MAX_PULSE_PERIOD {
+                            current = 0.0;
+                        } else {
+                            current = f32::min(current, pulse_distance / elapsed);
+                        }
+                    }
+                    if *count > 0.0 {
+                        last_pulse = Some(i);
+                    }
+                    speed.push(current);
+                }
+                speed
+            }
+            PulseKind::Period => data.iter()
+                .map(|period| {
+                    let period = period / 1_000_000.0;
+                    if period <= 0.0 || period > MAX_PULSE_PERIOD { 0.0 } else { pulse_distance / period }
+                })
+                .collect(),
+        }
+    }
+}